@@ -38,7 +38,7 @@ mod test {
             .is_equal_to(Some(VItem::Element {
                 name: String::from("div"),
                 attributes: vec![("class".into(), "container".into())],
-                text: None
+                listeners: Vec::new(),
             }));
         assert_that!(&parsed_tree.children(&parsed_root.unwrap()))
             .matching_contains(|node| {
@@ -48,4 +48,27 @@ mod test {
                 }
             });
     }
+
+    #[test]
+    fn test_parse_mixed_content() {
+
+        let parsed_tree: VTree = html! {
+            <p>"Hello " <b>"you"</b> "!"</p>
+        };
+
+        let parsed_root = parsed_tree.get_root().unwrap();
+        let children = parsed_tree.children(&parsed_root);
+
+        assert_that!(&parsed_tree.nodes()).has_length(5);
+        assert_that!(children.iter().map(|node| node.item.clone()).collect::<Vec<_>>())
+            .is_equal_to(vec![
+                Some(VItem::Text { value: String::from("Hello ") }),
+                Some(VItem::Element { name: String::from("b"), attributes: Vec::new(), listeners: Vec::new() }),
+                Some(VItem::Text { value: String::from("!") }),
+            ]);
+        assert_that!(parsed_tree.children(&children[1].id).iter().map(|node| node.item.clone()).collect::<Vec<_>>())
+            .is_equal_to(vec![
+                Some(VItem::Text { value: String::from("you") }),
+            ]);
+    }
 }
@@ -15,18 +15,23 @@ impl Html {
     }
 }
 
+#[derive(PartialEq, Debug)]
+pub enum HtmlNode {
+    Element(HtmlElement),
+    Text(String),
+}
+
 #[derive(PartialEq, Debug)]
 pub struct HtmlElement {
     name: String,
     attributes: Vec<HtmlAttribute>,
-    children: Vec<HtmlElement>,
-    text: Option<String>,
+    children: Vec<HtmlNode>,
 }
 
 impl HtmlElement {
 
-    pub fn new(name: String, attributes: Vec<HtmlAttribute>, children: Vec<HtmlElement>, text: Option<String>) -> HtmlElement {
-        HtmlElement { name, attributes, children, text }
+    pub fn new(name: String, attributes: Vec<HtmlAttribute>, children: Vec<HtmlNode>) -> HtmlElement {
+        HtmlElement { name, attributes, children }
     }
 
     pub fn add_attribute(&mut self, attribute: HtmlAttribute) {
@@ -34,22 +39,18 @@ impl HtmlElement {
     }
 
     pub fn add_child(&mut self, child: HtmlElement) {
-        self.children.push(child)
+        self.children.push(HtmlNode::Element(child))
     }
 
-    pub fn get_name(&self) -> &String {
-        &self.name
+    pub fn add_text(&mut self, text: String) {
+        self.children.push(HtmlNode::Text(text))
     }
 
-    pub fn set_text(&mut self, text: Option<String>) {
-        self.text = text
-    }
-
-    pub fn get_text(&self) -> &Option<String> {
-        &self.text
+    pub fn get_name(&self) -> &String {
+        &self.name
     }
 
-    pub fn children(&self) -> &Vec<HtmlElement> {
+    pub fn children(&self) -> &Vec<HtmlNode> {
         &self.children
     }
 
@@ -141,7 +141,7 @@ fn analyse_element_start(context: &mut AnalyseContext, token: &HtmlToken) -> Beh
         HtmlToken::Slash => Behavior::of(analyse_element_end),
         HtmlToken::Eq => Behavior::fail("Unexpected ="),
         HtmlToken::ElementStart { ident } => {
-            context.stack.push(HtmlElement::new(ident.to_string(), Vec::new(), Vec::new(), None));
+            context.stack.push(HtmlElement::new(ident.to_string(), Vec::new(), Vec::new()));
             Behavior::of(analyse_element_attributes)
         },
         HtmlToken::ElementEnd { .. } => Behavior::fail("Unexpected <"),
@@ -162,7 +162,7 @@ fn analyse_element_end(context: &mut AnalyseContext, token: &HtmlToken) -> Behav
                     match context.stack.last_mut() {
                         Some(parent) => {
                             parent.add_child(element);
-                            Behavior::of(initial)
+                            Behavior::of(analyse_element_content)
                         }
                         None => {
                             // Because their is no parent it must be the root element.
@@ -185,8 +185,8 @@ fn analyse_element_end(context: &mut AnalyseContext, token: &HtmlToken) -> Behav
             match context.stack.last_mut() {
                 None => Behavior::fail("Unexpected text"),
                 Some(element) => {
-                    element.set_text(Some(literal.value()));
-                    Behavior::Same
+                    element.add_text(literal.value());
+                    Behavior::of(analyse_element_content)
                 }
             }
         },
@@ -194,6 +194,29 @@ fn analyse_element_end(context: &mut AnalyseContext, token: &HtmlToken) -> Behav
     }
 }
 
+fn analyse_element_content(context: &mut AnalyseContext, token: &HtmlToken) -> Behavior {
+    match token {
+        HtmlToken::LessThan => Behavior::of(analyse_element_start),
+        HtmlToken::GreaterThan => Behavior::fail("Unexpected >"),
+        HtmlToken::Slash => Behavior::fail("Unexpected /"),
+        HtmlToken::Eq => Behavior::fail("Unexpected ="),
+        HtmlToken::ElementStart { .. } => Behavior::fail("Unexpected element start"),
+        HtmlToken::ElementEnd { .. } => Behavior::fail("Unexpected end of element"),
+        HtmlToken::AttributeName { .. } => Behavior::fail("Unexpected attribute name"),
+        HtmlToken::AttributeValue { .. } => Behavior::fail("Unexpected attribute value"),
+        HtmlToken::Text { literal } => {
+            match context.stack.last_mut() {
+                None => Behavior::fail("Unexpected text"),
+                Some(element) => {
+                    element.add_text(literal.value());
+                    Behavior::same()
+                }
+            }
+        },
+        HtmlToken::EOF => Behavior::unexpected("Got unexpected token while analysing the content of an element!", token)
+    }
+}
+
 fn analyse_element_attributes(context: &mut AnalyseContext, token: &HtmlToken) -> Behavior {
     match token {
         HtmlToken::LessThan => Behavior::fail("Unexpected <"),
@@ -252,7 +275,7 @@ mod test {
     use speculoos::prelude::*;
     use proc_macro2::{Ident, Span};
     use syn::LitStr;
-    use crate::html::{Html, HtmlAttribute, HtmlElement};
+    use crate::html::{Html, HtmlAttribute, HtmlElement, HtmlNode};
     use crate::html_analyse::{analyse_html, AnalyseError};
     use crate::html_parse::HtmlToken;
     use crate::HtmlTokenStream;
@@ -275,7 +298,7 @@ mod test {
 
         assert_that(&html).is_ok();
         assert_that(html.ok().unwrap().root())
-            .is_equal_to(HtmlElement::new(String::from("div"), Vec::new(), Vec::new(), None))
+            .is_equal_to(HtmlElement::new(String::from("div"), Vec::new(), Vec::new()))
     }
 
     #[test]
@@ -304,8 +327,7 @@ mod test {
                 vec![
                     HtmlAttribute::new(String::from("id"), Some(String::from("container")))
                 ],
-                Vec::new(),
-                None
+                Vec::new()
             ))
     }
 
@@ -325,7 +347,7 @@ mod test {
 
         assert_that(&html).is_ok();
         assert_that(html.ok().unwrap().root())
-            .is_equal_to(HtmlElement::new(String::from("img"), Vec::new(), Vec::new(), None))
+            .is_equal_to(HtmlElement::new(String::from("img"), Vec::new(), Vec::new()))
     }
 
     #[test]
@@ -357,14 +379,12 @@ mod test {
                 String::from("div"),
                 Vec::new(),
                 vec![
-                    HtmlElement::new(
+                    HtmlNode::Element(HtmlElement::new(
                         String::from("p"),
                         Vec::new(),
-                        Vec::new(),
-                        None
-                    ),
-                ],
-                None
+                        Vec::new()
+                    )),
+                ]
             ));
     }
 
@@ -390,8 +410,52 @@ mod test {
             .is_equal_to(HtmlElement::new(
                 String::from("div"),
                 Vec::new(),
+                vec![HtmlNode::Text(String::from("hello world"))]
+            ))
+    }
+
+    #[test]
+    fn test_analyse_element_with_mixed_content() {
+
+        let input = HtmlTokenStream::new(vec![
+            HtmlToken::LessThan,
+            HtmlToken::ElementStart { ident: Ident::new("p", Span::call_site()) },
+            HtmlToken::GreaterThan,
+            HtmlToken::Text { literal: LitStr::new("Hello ", Span::call_site()) },
+            HtmlToken::LessThan,
+            HtmlToken::ElementStart { ident: Ident::new("b", Span::call_site()) },
+            HtmlToken::GreaterThan,
+            HtmlToken::Text { literal: LitStr::new("you", Span::call_site()) },
+            HtmlToken::LessThan,
+            HtmlToken::Slash,
+            HtmlToken::ElementEnd { ident: Some(Ident::new("b", Span::call_site())) },
+            HtmlToken::GreaterThan,
+            HtmlToken::Text { literal: LitStr::new("!", Span::call_site()) },
+            HtmlToken::Text { literal: LitStr::new("?", Span::call_site()) },
+            HtmlToken::LessThan,
+            HtmlToken::Slash,
+            HtmlToken::ElementEnd { ident: Some(Ident::new("p", Span::call_site())) },
+            HtmlToken::GreaterThan,
+            HtmlToken::EOF
+        ]);
+
+        let html: Result<Html, AnalyseError> = analyse_html(input);
+
+        assert_that(&html).is_ok();
+        assert_that(html.ok().unwrap().root())
+            .is_equal_to(HtmlElement::new(
+                String::from("p"),
                 Vec::new(),
-                Some(String::from("hello world"))
+                vec![
+                    HtmlNode::Text(String::from("Hello ")),
+                    HtmlNode::Element(HtmlElement::new(
+                        String::from("b"),
+                        Vec::new(),
+                        vec![HtmlNode::Text(String::from("you"))]
+                    )),
+                    HtmlNode::Text(String::from("!")),
+                    HtmlNode::Text(String::from("?")),
+                ]
             ))
     }
 }
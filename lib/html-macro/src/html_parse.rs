@@ -127,7 +127,7 @@ impl HtmlToken {
                 Some(HtmlToken::Eq) => {
                     Ok(vec![HtmlToken::AttributeValue { literal }])
                 }
-                Some(HtmlToken::GreaterThan) => {
+                Some(HtmlToken::GreaterThan) | Some(HtmlToken::Text { .. }) => {
                     Ok(vec![HtmlToken::Text { literal }])
                 }
                 _ => {
@@ -335,6 +335,37 @@ mod test_html_token_parsing {
             ]);
     }
 
+    #[test]
+    fn test_parse_html_element_mixed_text() {
+
+        let html: HtmlTokenStream = parse_quote! {
+            <p>"Hello " "World" <b>"!"</b> "?"</p>
+        };
+
+        assert_that(&html.tokens)
+            .is_equal_to(&vec![
+                HtmlToken::LessThan,
+                HtmlToken::ElementStart { ident: Ident::new("p", Span::call_site()) },
+                HtmlToken::GreaterThan,
+                HtmlToken::Text { literal: LitStr::new("Hello ", Span::call_site()) },
+                HtmlToken::Text { literal: LitStr::new("World", Span::call_site()) },
+                HtmlToken::LessThan,
+                HtmlToken::ElementStart { ident: Ident::new("b", Span::call_site()) },
+                HtmlToken::GreaterThan,
+                HtmlToken::Text { literal: LitStr::new("!", Span::call_site()) },
+                HtmlToken::LessThan,
+                HtmlToken::Slash,
+                HtmlToken::ElementEnd { ident: Some(Ident::new("b", Span::call_site())) },
+                HtmlToken::GreaterThan,
+                HtmlToken::Text { literal: LitStr::new("?", Span::call_site()) },
+                HtmlToken::LessThan,
+                HtmlToken::Slash,
+                HtmlToken::ElementEnd { ident: Some(Ident::new("p", Span::call_site())) },
+                HtmlToken::GreaterThan,
+                HtmlToken::EOF
+            ]);
+    }
+
     #[test]
     fn test_parse_nested_html_elements() {
 
@@ -0,0 +1,92 @@
+use std::rc::Rc;
+
+use vdom::VRef;
+
+use crate::Cause;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Property {
+    Bool(bool),
+    Text(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Selection {
+    pub start: u32,
+    pub end: u32,
+    pub direction: Option<String>,
+}
+
+pub type Callback<E> = Rc<dyn Fn(&E)>;
+
+pub trait Backend: Clone + 'static {
+
+    type Node: Clone + 'static;
+    type Event: 'static;
+    // Removes the listener when dropped.
+    type Subscription: 'static;
+
+    fn create_element(&self, name: &str) -> Result<Self::Node, Cause>;
+
+    fn create_text(&self, value: &str) -> Result<Self::Node, Cause>;
+
+    fn tag_name(&self, node: &Self::Node) -> Option<String>;
+
+    fn text(&self, node: &Self::Node) -> Option<String>;
+
+    fn set_text(&self, node: &Self::Node, value: &str);
+
+    fn attribute_names(&self, node: &Self::Node) -> Vec<String>;
+
+    fn get_attribute(&self, node: &Self::Node, name: &str) -> Option<String>;
+
+    fn set_attribute(&self, node: &Self::Node, name: &str, value: &str) -> Result<(), Cause>;
+
+    fn remove_attribute(&self, node: &Self::Node, name: &str) -> Result<(), Cause>;
+
+    fn set_property(&self, node: &Self::Node, name: &str, value: Property) -> Result<(), Cause>;
+
+    fn parent(&self, node: &Self::Node) -> Option<Self::Node>;
+
+    fn first_child(&self, node: &Self::Node) -> Option<Self::Node>;
+
+    fn child_at(&self, node: &Self::Node, index: usize) -> Option<Self::Node>;
+
+    fn insert_before(&self, parent: &Self::Node, node: &Self::Node, before: Option<&Self::Node>) -> Result<(), Cause>;
+
+    fn remove_child(&self, parent: &Self::Node, node: &Self::Node) -> Result<(), Cause>;
+
+    fn replace_child(&self, parent: &Self::Node, node: &Self::Node, old: &Self::Node) -> Result<(), Cause>;
+
+    fn is_same(&self, node: &Self::Node, other: &Self::Node) -> bool;
+
+    fn set_ref(&self, node: &Self::Node, node_ref: &VRef) -> Result<(), Cause>;
+
+    fn get_ref(&self, node: &Self::Node) -> Option<VRef>;
+
+    fn active_element(&self) -> Option<Self::Node>;
+
+    // Restoring focus, selection and scroll offsets is best effort, failures are ignored.
+    fn focus(&self, node: &Self::Node);
+
+    fn selection(&self, node: &Self::Node) -> Option<Selection>;
+
+    fn set_selection(&self, node: &Self::Node, selection: &Selection);
+
+    fn scroll(&self, node: &Self::Node) -> (i32, i32);
+
+    fn set_scroll(&self, node: &Self::Node, offsets: (i32, i32));
+
+    // The rendered height in pixels, zero for nodes without a layout box.
+    fn height(&self, node: &Self::Node) -> f64;
+
+    fn listen(&self, node: &Self::Node, event: &str, capture: bool, callback: Callback<Self::Event>) -> Result<Self::Subscription, Cause>;
+
+    fn event_type(&self, event: &Self::Event) -> String;
+
+    fn event_target(&self, event: &Self::Event) -> Option<Self::Node>;
+
+    fn event_bubbles(&self, event: &Self::Event) -> bool;
+
+    fn propagation_stopped(&self, event: &Self::Event) -> bool;
+}
@@ -0,0 +1,122 @@
+use std::fmt::{Display, Formatter};
+
+use log::warn;
+use wasm_bindgen::JsValue;
+
+use vdom::VRef;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    MissingItem {
+        node: VRef,
+    },
+    MissingDocument,
+    Dom {
+        node: Option<VRef>,
+        operation: &'static str,
+        attribute: Option<String>,
+        cause: Cause,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cause {
+    Js(JsValue),
+    Message(String),
+}
+
+impl From<JsValue> for Cause {
+
+    fn from(value: JsValue) -> Self {
+        Cause::Js(value)
+    }
+}
+
+impl Display for Cause {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cause::Js(value) => write!(formatter, "{:?}", value),
+            Cause::Message(message) => write!(formatter, "{}", message),
+        }
+    }
+}
+
+impl Error {
+
+    pub(crate) fn dom(node: &VRef, operation: &'static str, cause: Cause) -> Error {
+        Error::Dom { node: Some(*node), operation, attribute: None, cause }
+    }
+
+    pub(crate) fn attribute(node: &VRef, operation: &'static str, attribute: &str, cause: Cause) -> Error {
+        Error::Dom { node: Some(*node), operation, attribute: Some(String::from(attribute)), cause }
+    }
+
+    pub(crate) fn container(operation: &'static str, cause: Cause) -> Error {
+        Error::Dom { node: None, operation, attribute: None, cause }
+    }
+
+    pub fn node(&self) -> Option<&VRef> {
+        match self {
+            Error::MissingItem { node } => Some(node),
+            Error::MissingDocument => None,
+            Error::Dom { node, .. } => node.as_ref(),
+        }
+    }
+}
+
+impl Display for Error {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::MissingItem { node } => {
+                write!(formatter, "Node {} has no item", String::from(node))
+            }
+            Error::MissingDocument => {
+                write!(formatter, "Container has no document")
+            }
+            Error::Dom { node, operation, attribute, cause } => {
+                write!(formatter, "Failed to {}", operation)?;
+                if let Some(attribute) = attribute {
+                    write!(formatter, " '{}'", attribute)?;
+                }
+                match node {
+                    Some(node) => write!(formatter, " of node {}", String::from(node))?,
+                    None => write!(formatter, " of the container")?,
+                }
+                write!(formatter, ": {}", cause)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for JsValue {
+
+    fn from(error: Error) -> Self {
+        JsValue::from_str(error.to_string().as_str())
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum ErrorMode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+impl ErrorMode {
+
+    // Strict mode passes the error on, lenient mode logs it and lets the caller skip the failed step.
+    pub(crate) fn check<T>(self, result: Result<T, Error>) -> Result<Option<T>, Error> {
+        match (self, result) {
+            (_, Ok(value)) => Ok(Some(value)),
+            (ErrorMode::Strict, Err(error)) => Err(error),
+            (ErrorMode::Lenient, Err(error)) => {
+                warn!("Skipping: {}", error);
+                Ok(None)
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use web_sys::Event;
+
+use vdom::{Listener, VRef};
+
+use crate::{Backend, Callback, Error, Mount};
+
+pub fn on<F>(event: &str, handler: F) -> Listener where F: Fn(&Event) + 'static {
+    Listener::new(event, move |event| {
+        if let Some(event) = event.downcast_ref::<Event>() {
+            handler(event)
+        }
+    })
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum EventMode {
+    #[default]
+    Direct,
+    Delegated,
+}
+
+pub(crate) struct Binding<B: Backend> {
+    target: B::Node,
+    slot: Rc<RefCell<Listener>>,
+    _subscription: B::Subscription,
+}
+
+impl<B: Backend> Binding<B> {
+
+    fn new(backend: &B, node_ref: &VRef, target: &B::Node, listener: &Listener) -> Result<Binding<B>, Error> {
+        let slot = Rc::new(RefCell::new(listener.clone()));
+        let callback: Callback<B::Event> = {
+            let slot = slot.clone();
+            Rc::new(move |event: &B::Event| {
+                // The listener is cloned out of the slot, so the handler may re-render
+                // and thereby swap the slot's content while it is running.
+                let listener = slot.borrow().clone();
+                listener.call(event);
+            })
+        };
+        let subscription = backend.listen(target, listener.event(), false, callback)
+            .map_err(|cause| Error::dom(node_ref, "add event listener", cause))?;
+        Ok(Binding {
+            target: target.clone(),
+            slot,
+            _subscription: subscription,
+        })
+    }
+
+    pub(crate) fn listener(&self) -> Listener {
+        self.slot.borrow().clone()
+    }
+
+    fn swap(&self, listener: &Listener) {
+        if *self.slot.borrow() != *listener {
+            self.slot.replace(listener.clone());
+        }
+    }
+}
+
+pub(crate) type Handlers = Rc<RefCell<HashMap<VRef, Vec<Listener>>>>;
+
+pub(crate) struct Delegation<B: Backend> {
+    handlers: Handlers,
+    delegates: HashMap<String, B::Subscription>,
+}
+
+impl<B: Backend> Default for Delegation<B> {
+
+    fn default() -> Self {
+        Delegation {
+            handlers: Rc::new(RefCell::new(HashMap::new())),
+            delegates: HashMap::new(),
+        }
+    }
+}
+
+fn delegate<B: Backend>(backend: &B, container: &B::Node, event: &str, handlers: &Handlers) -> Result<B::Subscription, Error> {
+    let callback: Callback<B::Event> = {
+        let backend = backend.clone();
+        let container = container.clone();
+        let handlers = handlers.clone();
+        Rc::new(move |event: &B::Event| dispatch(&backend, &container, &handlers, event))
+    };
+    // Events which do not bubble only reach the container during the capture phase.
+    backend.listen(container, event, !bubbles(event), callback)
+        .map_err(|cause| Error::container("add event listener", cause))
+}
+
+fn dispatch<B: Backend>(backend: &B, container: &B::Node, handlers: &Handlers, event: &B::Event) {
+    let event_type = backend.event_type(event);
+    let mut current = backend.event_target(event);
+    while let Some(node) = current {
+        if backend.is_same(&node, container) {
+            break
+        }
+        // The handlers are not borrowed while a listener runs, as it may re-render the mount.
+        let listener = backend.get_ref(&node).and_then(|node_ref| {
+            handlers.borrow().get(&node_ref)
+                .and_then(|listeners| listeners.iter().find(|listener| listener.event() == event_type).cloned())
+        });
+        if let Some(listener) = listener {
+            listener.call(event);
+        }
+        if !backend.event_bubbles(event) || backend.propagation_stopped(event) {
+            break
+        }
+        current = backend.parent(&node);
+    }
+}
+
+fn bubbles(event: &str) -> bool {
+    !matches!(event, "focus" | "blur" | "mouseenter" | "mouseleave" | "pointerenter" | "pointerleave" | "load" | "error" | "scroll")
+}
+
+impl<B: Backend> Mount<B> {
+
+    pub(crate) fn bind(&mut self, node_ref: &VRef, node: &B::Node, listeners: &[Listener]) -> Result<(), Error> {
+        match self.events {
+            EventMode::Direct => self.bind_direct(node_ref, node, listeners),
+            EventMode::Delegated => self.bind_delegated(node_ref, listeners),
+        }
+    }
+
+    pub(crate) fn unbind(&mut self, node_ref: &VRef) {
+        self.listeners.remove(node_ref);
+        self.delegation.handlers.borrow_mut().remove(node_ref);
+    }
+
+    pub(crate) fn bound_listeners(&self, node_ref: &VRef) -> Vec<Listener> {
+        match self.events {
+            EventMode::Direct => self.listeners.get(node_ref)
+                .map(|bindings| bindings.values().map(|binding| binding.listener()).collect())
+                .unwrap_or_default(),
+            EventMode::Delegated => self.delegation.handlers.borrow().get(node_ref)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    fn bind_direct(&mut self, node_ref: &VRef, node: &B::Node, listeners: &[Listener]) -> Result<(), Error> {
+        let mode = self.errors;
+        let backend = &self.backend;
+        let bindings = self.listeners.entry(*node_ref).or_default();
+        bindings.retain(|event, binding| {
+            backend.is_same(&binding.target, node) && listeners.iter().any(|listener| listener.event() == event)
+        });
+        let mut result = Ok(());
+        for listener in listeners.iter() {
+            match bindings.get(listener.event()) {
+                Some(binding) => binding.swap(listener),
+                None => {
+                    match mode.check(Binding::new(backend, node_ref, node, listener)) {
+                        Ok(Some(binding)) => {
+                            bindings.insert(String::from(listener.event()), binding);
+                        }
+                        Ok(None) => {}
+                        Err(error) => {
+                            result = Err(error);
+                            break
+                        }
+                    }
+                }
+            }
+        }
+        if bindings.is_empty() {
+            self.listeners.remove(node_ref);
+        }
+        result
+    }
+
+    fn bind_delegated(&mut self, node_ref: &VRef, listeners: &[Listener]) -> Result<(), Error> {
+        if listeners.is_empty() {
+            self.delegation.handlers.borrow_mut().remove(node_ref);
+            return Ok(())
+        }
+        self.delegation.handlers.borrow_mut().insert(*node_ref, listeners.to_vec());
+        for listener in listeners {
+            if !self.delegation.delegates.contains_key(listener.event()) {
+                let subscription = delegate(&self.backend, &self.container, listener.event(), &self.delegation.handlers);
+                if let Some(subscription) = self.errors.check(subscription)? {
+                    self.delegation.delegates.insert(String::from(listener.event()), subscription);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) type Bindings<B> = HashMap<VRef, HashMap<String, Binding<B>>>;
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{Listener, VRef, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::{EventMode, MemoryEvent, MountOptions, Operations};
+    use crate::testing::memory_mount;
+
+    fn record(calls: &Rc<RefCell<Vec<String>>>, name: &str, stop: bool) -> Listener {
+        let calls = calls.clone();
+        let name = String::from(name);
+        Listener::new("click", move |event| {
+            calls.borrow_mut().push(name.clone());
+            if let (true, Some(event)) = (stop, event.downcast_ref::<MemoryEvent>()) {
+                event.stop_propagation()
+            }
+        })
+    }
+
+    fn create_tree(calls: &Rc<RefCell<Vec<String>>>, stop: bool) -> (VTree, VRef, VRef) {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let item = element(&mut tree, Some(&list), "li", &[]);
+        let label = text(&mut tree, &item, "Item");
+        if let Some(list) = tree.item_mut(&list) {
+            list.add_listener(record(calls, "list", false));
+        }
+        if let Some(item) = tree.item_mut(&item) {
+            item.add_listener(record(calls, "item", stop));
+        }
+        (tree, item, label)
+    }
+
+    #[test]
+    fn test_direct_events() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (tree, item, _) = create_tree(&calls, false);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let node = mount.get(&item).cloned().expect("item mounted");
+        node.dispatch("click");
+
+        assert_that!(*calls.borrow()).is_equal_to(vec![String::from("item"), String::from("list")]);
+        assert_that!(node.listener_count()).is_equal_to(1);
+        assert_that!(mount.container().listener_count()).is_equal_to(0);
+
+        mount.unmount().expect("unmounted");
+
+        assert_that!(node.listener_count()).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_delegated_events() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (tree, item, label) = create_tree(&calls, false);
+        let mut mount = memory_mount(MountOptions { events: EventMode::Delegated, ..MountOptions::default() });
+        mount.update(&tree).expect("mounted");
+
+        // Every node is tagged once, binding the delegated listeners does not tag it again.
+        assert_that!(mount.operations().refs).is_equal_to(3);
+
+        mount.reset_operations();
+        mount.update(&tree).expect("rendered again");
+        mount.get(&label).expect("label mounted").dispatch("click");
+
+        assert_that!(mount.operations()).is_equal_to(Operations::default());
+        assert_that!(*calls.borrow()).is_equal_to(vec![String::from("item"), String::from("list")]);
+        assert_that!(mount.get(&item).expect("item mounted").listener_count()).is_equal_to(0);
+        assert_that!(mount.container().listener_count()).is_equal_to(1);
+
+        mount.unmount().expect("unmounted");
+
+        assert_that!(mount.container().listener_count()).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_delegation_listens_once_per_event() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let items: Vec<VRef> = (0..100)
+            .map(|index| {
+                let item = element(&mut tree, Some(&list), "li", &[]);
+                text(&mut tree, &item, index.to_string().as_str());
+                if let Some(item) = tree.item_mut(&item) {
+                    item.add_listener(record(&calls, index.to_string().as_str(), false));
+                    item.add_listener(Listener::new("mouseenter", |_| {}));
+                }
+                item
+            })
+            .collect();
+        let mut mount = memory_mount(MountOptions { events: EventMode::Delegated, ..MountOptions::default() });
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(mount.container().listener_count()).is_equal_to(2);
+        assert_that!(items.iter().all(|item| mount.get(item).is_some_and(|node| node.listener_count() == 0))).is_true();
+
+        mount.get(&items[57]).expect("item mounted").children()[0].dispatch("click");
+
+        assert_that!(*calls.borrow()).is_equal_to(vec![String::from("57")]);
+    }
+
+    #[test]
+    fn test_delegated_stop_propagation() {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let (tree, item, _) = create_tree(&calls, true);
+        let mut mount = memory_mount(MountOptions { events: EventMode::Delegated, ..MountOptions::default() });
+        mount.update(&tree).expect("mounted");
+
+        mount.get(&item).expect("item mounted").dispatch("click");
+
+        assert_that!(*calls.borrow()).is_equal_to(vec![String::from("item")]);
+    }
+}
@@ -0,0 +1,394 @@
+use web_sys::Element;
+
+use vdom::{VItem, VRef, VTree};
+
+use crate::{Backend, Error, Mount, MountOptions, VNodeLink};
+use crate::mount::detach;
+use crate::props;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Mismatch {
+    MissingNode {
+        node: VRef,
+    },
+    UnexpectedNode {
+        parent: VRef,
+    },
+    DifferentNode {
+        node: VRef,
+    },
+    DifferentAttribute {
+        node: VRef,
+        name: String,
+        expected: Option<String>,
+        found: Option<String>,
+    },
+    DifferentText {
+        node: VRef,
+        expected: String,
+        found: String,
+    },
+}
+
+impl<B: Backend> Mount<B> {
+
+    // Adopts the DOM below the container instead of recreating it. Every mismatch is
+    // repaired, so the DOM matches the tree afterwards, and reported.
+    pub fn hydrate(&mut self, tree: &VTree) -> Result<Vec<Mismatch>, Error> {
+        let mut recorder = self.record("hydrate");
+        let mut mismatches = Vec::new();
+        self.root = tree.get_root();
+        let mut result = Ok(());
+        if let Some(root) = tree.get_root() {
+            // Other content of the container is left alone, it may not belong to this mount.
+            let existing = self.child_nodes(&self.container).into_iter().find(|child| !self.is_formatting(child));
+            let container = self.container.clone();
+            result = self.hydrate_child(tree, &root, &container, existing, None, &mut mismatches);
+        }
+        recorder.lap("hydrate");
+        self.finish(recorder);
+        result.map(|_| mismatches)
+    }
+
+    // Adopts the existing node if it matches, otherwise a new node replaces it. Without an existing
+    // node a new one goes in before the given sibling.
+    fn hydrate_child(&mut self, tree: &VTree, node_ref: &VRef, parent: &B::Node, existing: Option<B::Node>, before: Option<&B::Node>, mismatches: &mut Vec<Mismatch>) -> Result<(), Error> {
+        let item = match tree.item(node_ref) {
+            Some(item) => item,
+            None => return Ok(()),
+        };
+        match existing {
+            Some(existing) if self.matches(item, &existing) => {
+                self.compare(node_ref, item, &existing, mismatches);
+                self.adopt(tree, node_ref, existing, mismatches)
+            }
+            Some(existing) => {
+                mismatches.push(Mismatch::DifferentNode { node: *node_ref });
+                if let Some(node) = self.render(tree, node_ref)? {
+                    let replaced = self.backend.replace_child(parent, &node, &existing)
+                        .map_err(|cause| Error::dom(node_ref, "replace node", cause));
+                    self.errors.check(replaced)?;
+                }
+                Ok(())
+            }
+            None => {
+                mismatches.push(Mismatch::MissingNode { node: *node_ref });
+                self.insert_missing(tree, node_ref, parent, before)
+            }
+        }
+    }
+
+    fn adopt(&mut self, tree: &VTree, node_ref: &VRef, existing: B::Node, mismatches: &mut Vec<Mismatch>) -> Result<(), Error> {
+        let vnode = match tree.get_node(node_ref) {
+            Some(vnode) => vnode,
+            None => return Ok(()),
+        };
+        let before = self.backend.operations();
+        let unsettled = vnode.item.as_ref().is_some_and(|item| props::unsettled(&self.backend, Some(&existing), item));
+        let node = match self.errors.check(vnode.upsert(&self.backend, Some(&existing), self.errors))? {
+            Some(node) => node,
+            None => return Ok(()),
+        };
+        self.track_update(node_ref, before);
+        self.hydrate_children(tree, node_ref, &node, mismatches)?;
+        if unsettled {
+            let settled = props::settle(&self.backend, &node)
+                .map_err(|cause| Error::attribute(node_ref, "set property", "value", cause));
+            self.errors.check(settled)?;
+        }
+
+        if let Some(item) = &vnode.item {
+            self.bind(node_ref, &node, item.listeners())?;
+        }
+        self.track(node_ref, node);
+        Ok(())
+    }
+
+    fn hydrate_children(&mut self, tree: &VTree, node_ref: &VRef, node: &B::Node, mismatches: &mut Vec<Mismatch>) -> Result<(), Error> {
+        let children: Vec<VRef> = tree.children(node_ref).iter()
+            .filter(|child| child.item.is_some())
+            .map(|child| child.id)
+            .collect();
+        let is_text = |child: &VRef| matches!(tree.item(child), Some(VItem::Text { .. }));
+        let mut dom_children = self.child_nodes(node).into_iter().peekable();
+        let mut index = 0;
+        while index < children.len() {
+            let run = children[index..].iter().take_while(|child| is_text(child)).count();
+            if run > 0 {
+                let mut texts = Vec::new();
+                while let Some(text) = dom_children.next_if(|child| self.backend.text(child).is_some()) {
+                    texts.push(text);
+                }
+                self.hydrate_texts(tree, &children[index..index + run], node, texts, dom_children.peek(), mismatches)?;
+                index += run;
+                continue
+            }
+            while dom_children.next_if(|child| self.is_formatting(child)).is_some() {}
+            let child = &children[index];
+            // A DOM node matching one of the following children is left for it, the current child is missing in between.
+            let missing = dom_children.peek().is_some_and(|existing| {
+                tree.item(child).is_some_and(|item| !self.matches(item, existing))
+                    && children[index + 1..].iter().filter_map(|next| tree.item(next)).any(|next| self.matches(next, existing))
+            });
+            let existing = if missing { None } else { dom_children.next() };
+            self.hydrate_child(tree, child, node, existing, dom_children.peek(), mismatches)?;
+            index += 1;
+        }
+        let unexpected: Vec<B::Node> = dom_children.filter(|child| !self.is_formatting(child)).collect();
+        for unexpected in unexpected {
+            mismatches.push(Mismatch::UnexpectedNode { parent: *node_ref });
+            self.errors.check(detach(&self.backend, node_ref, &unexpected))?;
+        }
+        Ok(())
+    }
+
+    // Servers render adjacent text nodes as one text, which the parser cannot split again. So a run of
+    // text nodes in the tree is matched against all adjacent DOM text nodes and only the joined texts have to agree.
+    fn hydrate_texts(&mut self, tree: &VTree, run: &[VRef], parent: &B::Node, texts: Vec<B::Node>, before: Option<&B::Node>, mismatches: &mut Vec<Mismatch>) -> Result<(), Error> {
+        if texts.is_empty() {
+            for node_ref in run {
+                mismatches.push(Mismatch::MissingNode { node: *node_ref });
+                self.insert_missing(tree, node_ref, parent, before)?;
+            }
+            return Ok(())
+        }
+        let expected: String = run.iter()
+            .filter_map(|node_ref| match tree.item(node_ref) {
+                Some(VItem::Text { value }) => Some(value.as_str()),
+                _ => None,
+            })
+            .collect();
+        let found: String = texts.iter().filter_map(|text| self.backend.text(text)).collect();
+        if expected != found {
+            mismatches.push(Mismatch::DifferentText { node: run[0], expected, found });
+        }
+        let mut texts = texts.into_iter();
+        for node_ref in run {
+            match texts.next() {
+                Some(text) => self.adopt(tree, node_ref, text, mismatches)?,
+                None => self.insert_missing(tree, node_ref, parent, before)?,
+            }
+        }
+        for text in texts {
+            self.errors.check(detach(&self.backend, &run[0], &text))?;
+        }
+        Ok(())
+    }
+
+    fn insert_missing(&mut self, tree: &VTree, node_ref: &VRef, parent: &B::Node, before: Option<&B::Node>) -> Result<(), Error> {
+        if let Some(node) = self.render(tree, node_ref)? {
+            let inserted = self.backend.insert_before(parent, &node, before)
+                .map_err(|cause| Error::dom(node_ref, "insert node", cause));
+            self.errors.check(inserted)?;
+        }
+        Ok(())
+    }
+
+    fn matches(&self, item: &VItem, node: &B::Node) -> bool {
+        match item {
+            VItem::Element { name, .. } => self.backend.tag_name(node).is_some_and(|tag_name| tag_name.eq_ignore_ascii_case(name)),
+            VItem::Text { .. } => self.backend.text(node).is_some(),
+        }
+    }
+
+    fn compare(&self, node_ref: &VRef, item: &VItem, node: &B::Node, mismatches: &mut Vec<Mismatch>) {
+        match item {
+            VItem::Element { attributes, .. } => {
+                attributes.iter()
+                    .filter(|(name, value)| props::changed(&self.backend, node, name, value))
+                    .for_each(|(name, value)| mismatches.push(Mismatch::DifferentAttribute {
+                        node: *node_ref,
+                        name: name.clone(),
+                        expected: Some(value.clone()),
+                        found: self.backend.get_attribute(node, name),
+                    }));
+                self.backend.attribute_names(node).into_iter()
+                    .filter(|name| !attributes.iter().any(|(attribute, _)| attribute.eq_ignore_ascii_case(name)))
+                    .for_each(|name| mismatches.push(Mismatch::DifferentAttribute {
+                        node: *node_ref,
+                        found: self.backend.get_attribute(node, name.as_str()),
+                        name,
+                        expected: None,
+                    }));
+            }
+            VItem::Text { value } => {
+                let found = self.backend.text(node).unwrap_or_default();
+                if found != *value {
+                    mismatches.push(Mismatch::DifferentText { node: *node_ref, expected: value.clone(), found });
+                }
+            }
+        }
+    }
+
+    fn child_nodes(&self, node: &B::Node) -> Vec<B::Node> {
+        let mut children = Vec::new();
+        while let Some(child) = self.backend.child_at(node, children.len()) {
+            children.push(child);
+        }
+        children
+    }
+
+    // Formatting whitespace between the elements of server-rendered markup has no counterpart in the tree.
+    fn is_formatting(&self, node: &B::Node) -> bool {
+        self.backend.text(node).is_some_and(|text| text.trim().is_empty())
+    }
+}
+
+pub fn hydrate(tree: &VTree, container: &Element, options: MountOptions) -> Result<(Mount, Vec<Mismatch>), Error> {
+    let mut mount = Mount::with_options(container, options)?;
+    let mismatches = mount.hydrate(tree)?;
+    Ok((mount, mismatches))
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{Listener, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::{Backend, MemoryBackend, MemoryNode, Mismatch, Mount, MountOptions, Operations};
+    use crate::testing::{html, memory_mount};
+
+    fn server_render(tree: &VTree) -> MemoryNode {
+        let mut server = memory_mount(MountOptions::default());
+        server.update(tree).expect("rendered");
+        server.container().clone()
+    }
+
+    #[test]
+    fn test_hydrate() {
+        let mut server_tree = VTree::new();
+        let div = element(&mut server_tree, None, "div", &[("class", "box")]);
+        let button = element(&mut server_tree, Some(&div), "button", &[]);
+        text(&mut server_tree, &button, "Login");
+        let container = server_render(&server_tree);
+        let backend = MemoryBackend::new();
+        let root = container.children()[0].clone();
+        let formatting = backend.create_text("\n  ").expect("text created");
+        backend.insert_before(&root, &formatting, root.children().first()).expect("inserted");
+
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "box")]);
+        let button = element(&mut tree, Some(&div), "button", &[]);
+        text(&mut tree, &button, "Login");
+        let clicks = Rc::new(RefCell::new(0));
+        if let Some(item) = tree.item_mut(&button) {
+            let clicks = clicks.clone();
+            item.add_listener(Listener::new("click", move |_| *clicks.borrow_mut() += 1));
+        }
+        let mut mount = Mount::with_backend(backend, &container, MountOptions::default());
+        let mismatches = mount.hydrate(&tree).expect("hydrated");
+
+        assert_that!(mismatches).is_empty();
+        assert_that!(mount.operations().created).is_equal_to(0);
+        assert_that!(mount.len()).is_equal_to(3);
+        assert_that!(mount.root().is_some_and(|node| node.is_same(&root))).is_true();
+
+        mount.get(&button).expect("button hydrated").dispatch("click");
+
+        assert_that!(*clicks.borrow()).is_equal_to(1);
+    }
+
+    #[test]
+    fn test_hydrate_mismatches() {
+        let mut server_tree = VTree::new();
+        let div = element(&mut server_tree, None, "div", &[("class", "old"), ("data-server", "1")]);
+        let title = element(&mut server_tree, Some(&div), "h1", &[]);
+        text(&mut server_tree, &title, "Hello");
+        element(&mut server_tree, Some(&div), "span", &[]);
+        element(&mut server_tree, Some(&div), "footer", &[]);
+        element(&mut server_tree, Some(&div), "aside", &[]);
+        let container = server_render(&server_tree);
+
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "new")]);
+        let title = element(&mut tree, Some(&div), "h1", &[]);
+        let greeting = text(&mut tree, &title, "Hello World");
+        let paragraph = element(&mut tree, Some(&div), "p", &[]);
+        element(&mut tree, Some(&div), "footer", &[]);
+        let mut mount = Mount::with_backend(MemoryBackend::new(), &container, MountOptions::default());
+        let mismatches = mount.hydrate(&tree).expect("hydrated");
+
+        assert_that!(mismatches).is_equal_to(vec![
+            Mismatch::DifferentAttribute { node: div, name: String::from("class"), expected: Some(String::from("new")), found: Some(String::from("old")) },
+            Mismatch::DifferentAttribute { node: div, name: String::from("data-server"), expected: None, found: Some(String::from("1")) },
+            Mismatch::DifferentText { node: greeting, expected: String::from("Hello World"), found: String::from("Hello") },
+            Mismatch::DifferentNode { node: paragraph },
+            Mismatch::UnexpectedNode { parent: div },
+        ]);
+        assert_that!(html(&mount)).is_equal_to(String::from("<div class=\"new\"><h1>Hello World</h1><p></p><footer></footer></div>"));
+        assert_that!(mount.len()).is_equal_to(5);
+    }
+
+    #[test]
+    fn test_hydrate_whitespace_text() {
+        let mut tree = VTree::new();
+        let paragraph = element(&mut tree, None, "p", &[]);
+        text(&mut tree, &paragraph, "a");
+        let space = text(&mut tree, &paragraph, " ");
+        element(&mut tree, Some(&paragraph), "b", &[]);
+        let container = server_render(&tree);
+        let mut mount = Mount::with_backend(MemoryBackend::new(), &container, MountOptions::default());
+        let mismatches = mount.hydrate(&tree).expect("hydrated");
+
+        assert_that!(mismatches).is_empty();
+        // Adopted nodes are only tagged with their VRef.
+        assert_that!(mount.operations()).is_equal_to(Operations { refs: 4, ..Operations::default() });
+        assert_that!(html(&mount)).is_equal_to(String::from("<p>a <b></b></p>"));
+        assert_that!(mount.get(&space).and_then(|node| node.value())).is_equal_to(Some(String::from(" ")));
+    }
+
+    #[test]
+    fn test_hydrate_merged_text() {
+        let mut server_tree = VTree::new();
+        let paragraph = element(&mut server_tree, None, "p", &[]);
+        text(&mut server_tree, &paragraph, "Hello World");
+        element(&mut server_tree, Some(&paragraph), "b", &[]);
+        let container = server_render(&server_tree);
+
+        let mut tree = VTree::new();
+        let paragraph = element(&mut tree, None, "p", &[]);
+        let hello = text(&mut tree, &paragraph, "Hello ");
+        let world = text(&mut tree, &paragraph, "World");
+        element(&mut tree, Some(&paragraph), "b", &[]);
+        let mut mount = Mount::with_backend(MemoryBackend::new(), &container, MountOptions::default());
+        let mismatches = mount.hydrate(&tree).expect("hydrated");
+
+        assert_that!(mismatches).is_empty();
+        assert_that!(mount.operations().created).is_equal_to(1);
+        assert_that!(html(&mount)).is_equal_to(String::from("<p>Hello World<b></b></p>"));
+        assert_that!(mount.get(&hello).and_then(|node| node.value())).is_equal_to(Some(String::from("Hello ")));
+        assert_that!(mount.get(&world).and_then(|node| node.value())).is_equal_to(Some(String::from("World")));
+        assert_that!(container.children()[0].children().len()).is_equal_to(3);
+    }
+
+    #[test]
+    fn test_hydrate_missing_node() {
+        let mut server_tree = VTree::new();
+        let div = element(&mut server_tree, None, "div", &[]);
+        element(&mut server_tree, Some(&div), "h1", &[]);
+        element(&mut server_tree, Some(&div), "footer", &[]);
+        let container = server_render(&server_tree);
+
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        element(&mut tree, Some(&div), "h1", &[]);
+        let paragraph = element(&mut tree, Some(&div), "p", &[]);
+        let label = text(&mut tree, &div, "label");
+        let footer = element(&mut tree, Some(&div), "footer", &[]);
+        let mut mount = Mount::with_backend(MemoryBackend::new(), &container, MountOptions::default());
+        let footer_node = container.children()[0].children()[1].clone();
+        let mismatches = mount.hydrate(&tree).expect("hydrated");
+
+        assert_that!(mismatches).is_equal_to(vec![
+            Mismatch::MissingNode { node: paragraph },
+            Mismatch::MissingNode { node: label },
+        ]);
+        assert_that!(html(&mount)).is_equal_to(String::from("<div><h1></h1><p></p>label<footer></footer></div>"));
+        assert_that!(mount.get(&footer).is_some_and(|node| node.is_same(&footer_node))).is_true();
+    }
+}
@@ -1,39 +1,104 @@
-use web_sys::{Document, Element};
+use vdom::{VItem, VNode, VRef};
 
-use vdom::{VItem, VNode};
+pub use backend::{Backend, Callback, Property, Selection};
+pub use error::{Cause, Error, ErrorMode};
+pub use events::{on, EventMode};
+pub use hydrate::{hydrate, Mismatch};
+pub use memory::{MemoryBackend, MemoryEvent, MemoryNode, MemorySubscription};
+pub use mount::{mount, mount_with_options, Cleanup, Mount, MountOptions};
+pub use operations::Operations;
+pub use report::RenderReport;
+pub use shadow::mount_in_shadow_root;
+pub use schedule::{AnimationFrames, FrameSource, Invalidator, Scheduler, Task};
+pub use slice::{Clock, PerformanceClock, Progress, RenderHandle, SlicedRender, TimeSlicer};
+pub use transition::{Timer, Timing, Transition, WindowTimer};
+pub use virtual_list::{Row, RowHeight, VirtualList};
+pub use web::{WebBackend, WebSubscription};
+
+mod backend;
+mod error;
+mod events;
+mod hydrate;
+mod memory;
+mod mount;
+mod operations;
+mod patch;
+mod props;
+mod report;
+mod schedule;
+mod shadow;
+mod slice;
+mod state;
+#[cfg(test)]
+mod testing;
+mod transition;
+mod virtual_list;
+mod web;
 
 pub trait VNodeLink {
 
-    fn upsert(&self, document: &Document) -> Element;
+    fn upsert<B: Backend>(&self, backend: &B, existing: Option<&B::Node>, mode: ErrorMode) -> Result<B::Node, Error>;
 }
 
 impl VNodeLink for VNode {
 
-    fn upsert(&self, document: &Document) -> Element {
-        let id: String = self.id.into();
+    fn upsert<B: Backend>(&self, backend: &B, existing: Option<&B::Node>, mode: ErrorMode) -> Result<B::Node, Error> {
         let item: &Option<VItem> = &self.item;
-        let element = match document.get_element_by_id(id.as_str()) {
-            None => {
-                match item {
-                    None => { panic!("No item") }
-                    Some(VItem::Element { name, attributes, text }) => {
-                        let element = document.create_element(name).expect("element created");
-                        element.set_id(id.as_str());
-                        attributes.iter().for_each(|(name, value)| {
-                            element.set_attribute(name.as_str(), value.as_str());
-                        });
-                        if let Some(value) = text {
-                            element.set_text_content(Some(value.as_str()))
+        match item {
+            None => Err(Error::MissingItem { node: self.id }),
+            Some(VItem::Element { name, attributes, .. }) => {
+                let existing_element = existing
+                    .filter(|node| backend.tag_name(node).is_some_and(|tag_name| tag_name.eq_ignore_ascii_case(name)));
+                match existing_element {
+                    None => {
+                        let element = backend.create_element(name)
+                            .map_err(|cause| Error::dom(&self.id, "create element", cause))?;
+                        for (name, value) in attributes.iter() {
+                            let set = props::set(backend, &element, name, value)
+                                .map_err(|cause| Error::attribute(&self.id, "set attribute", name, cause));
+                            mode.check(set)?;
                         }
-                        element
+                        Ok(element)
+                    }
+                    Some(element) => {
+                        reconcile_attributes(backend, &self.id, element, attributes, mode)?;
+                        Ok(element.clone())
                     }
-                    Some(VItem::Text { .. }) => {
-                        panic!("VItem::Text not supported!")
+                }
+            }
+            Some(VItem::Text { value }) => {
+                match existing.filter(|node| backend.text(node).is_some()) {
+                    None => {
+                        backend.create_text(value.as_str())
+                            .map_err(|cause| Error::dom(&self.id, "create text", cause))
+                    }
+                    Some(text) => {
+                        if backend.text(text).as_ref() != Some(value) {
+                            backend.set_text(text, value.as_str());
+                        }
+                        Ok(text.clone())
                     }
                 }
-            },
-            Some(element) => element,
-        };
-        element
+            }
+        }
+    }
+}
+
+fn reconcile_attributes<B: Backend>(backend: &B, node: &VRef, element: &B::Node, attributes: &[(String, String)], mode: ErrorMode) -> Result<(), Error> {
+    let stale: Vec<String> = backend.attribute_names(element).into_iter()
+        .filter(|name| !attributes.iter().any(|(attribute, _)| attribute.eq_ignore_ascii_case(name)))
+        .collect();
+    for name in stale.iter() {
+        let removed = props::remove(backend, element, name)
+            .map_err(|cause| Error::attribute(node, "remove attribute", name, cause));
+        mode.check(removed)?;
+    }
+    for (name, value) in attributes.iter() {
+        if props::changed(backend, element, name, value) {
+            let set = props::set(backend, element, name, value)
+                .map_err(|cause| Error::attribute(node, "set attribute", name, cause));
+            mode.check(set)?;
+        }
     }
+    Ok(())
 }
@@ -0,0 +1,678 @@
+use std::cell::{Cell, RefCell};
+use std::fmt::{Debug, Formatter};
+use std::rc::{Rc, Weak};
+
+use vdom::VRef;
+
+use crate::{Backend, Callback, Cause, Property, Selection};
+
+#[derive(Clone, Default)]
+pub struct MemoryBackend {
+    active: Rc<RefCell<Option<MemoryNode>>>,
+    // Like the web backend, one for a shadow root sees which of its nodes is focused.
+    shadow_root: Option<MemoryNode>,
+}
+
+impl MemoryBackend {
+
+    pub fn new() -> MemoryBackend {
+        MemoryBackend::default()
+    }
+
+    // Shares the focus with this backend, as both are in the same document.
+    pub fn for_shadow_root(&self, shadow_root: &MemoryNode) -> MemoryBackend {
+        MemoryBackend { active: self.active.clone(), shadow_root: Some(shadow_root.clone()) }
+    }
+
+    // Like a browser, nodes leaving the tree lose focus and their scroll offsets.
+    fn detached(&self, node: &MemoryNode) {
+        let active = self.active.borrow().clone();
+        if active.is_some_and(|active| node.is_inclusive_ancestor_of(&active)) {
+            self.active.replace(None);
+        }
+        node.reset_scroll();
+    }
+}
+
+#[derive(Clone)]
+pub struct MemoryNode {
+    data: Rc<RefCell<NodeData>>,
+}
+
+struct NodeData {
+    kind: Kind,
+    parent: Weak<RefCell<NodeData>>,
+    // The element a shadow root is attached to, and the shadow root attached to an element.
+    host: Weak<RefCell<NodeData>>,
+    shadow_root: Option<MemoryNode>,
+    children: Vec<MemoryNode>,
+    vref: Option<VRef>,
+    listeners: Vec<MemoryListener>,
+    next_listener: usize,
+    selection: Option<Selection>,
+    scroll: (i32, i32),
+    height: f64,
+}
+
+enum Kind {
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        properties: Vec<(String, Property)>,
+    },
+    Text {
+        value: String,
+    },
+    ShadowRoot {
+        open: bool,
+    },
+}
+
+struct MemoryListener {
+    id: usize,
+    event: String,
+    capture: bool,
+    callback: Callback<MemoryEvent>,
+}
+
+impl MemoryNode {
+
+    pub fn element(name: &str) -> MemoryNode {
+        MemoryNode::with_kind(Kind::Element { name: String::from(name), attributes: Vec::new(), properties: Vec::new() })
+    }
+
+    pub fn text(value: &str) -> MemoryNode {
+        MemoryNode::with_kind(Kind::Text { value: String::from(value) })
+    }
+
+    fn with_kind(kind: Kind) -> MemoryNode {
+        MemoryNode {
+            data: Rc::new(RefCell::new(NodeData {
+                kind,
+                parent: Weak::new(),
+                host: Weak::new(),
+                shadow_root: None,
+                children: Vec::new(),
+                vref: None,
+                listeners: Vec::new(),
+                next_listener: 0,
+                selection: None,
+                scroll: (0, 0),
+                height: 0.0,
+            }))
+        }
+    }
+
+    pub fn name(&self) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Element { name, .. } => Some(name.clone()),
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Element { .. } | Kind::ShadowRoot { .. } => None,
+            Kind::Text { value } => Some(value.clone()),
+        }
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Element { attributes, .. } => attributes.iter()
+                .find(|(attribute, _)| attribute == name)
+                .map(|(_, value)| value.clone()),
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => None,
+        }
+    }
+
+    pub fn property(&self, name: &str) -> Option<Property> {
+        match &self.data.borrow().kind {
+            Kind::Element { properties, .. } => properties.iter()
+                .find(|(property, _)| property == name)
+                .map(|(_, value)| value.clone()),
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => None,
+        }
+    }
+
+    // Changes a property the way user input would, without touching the attributes.
+    pub fn set_property(&self, name: &str, value: Property) {
+        if let Kind::Element { properties, .. } = &mut self.data.borrow_mut().kind {
+            match properties.iter_mut().find(|(property, _)| property == name) {
+                Some((_, existing)) => *existing = value,
+                None => properties.push((String::from(name), value)),
+            }
+        }
+    }
+
+    // There is no layout, so heights are whatever the test says they are.
+    pub fn set_height(&self, height: f64) {
+        self.data.borrow_mut().height = height;
+    }
+
+    pub fn parent(&self) -> Option<MemoryNode> {
+        self.data.borrow().parent.upgrade().map(|data| MemoryNode { data })
+    }
+
+    // Fails for text nodes and elements which already have a shadow root, like in a browser.
+    pub fn attach_shadow(&self, open: bool) -> Result<MemoryNode, Cause> {
+        if self.name().is_none() || self.data.borrow().shadow_root.is_some() {
+            return Err(Cause::Message(String::from("cannot attach a shadow root")))
+        }
+        let shadow_root = MemoryNode::with_kind(Kind::ShadowRoot { open });
+        shadow_root.data.borrow_mut().host = Rc::downgrade(&self.data);
+        self.data.borrow_mut().shadow_root = Some(shadow_root.clone());
+        Ok(shadow_root)
+    }
+
+    // Closed shadow roots are only reachable through whoever attached them.
+    pub fn shadow_root(&self) -> Option<MemoryNode> {
+        self.data.borrow().shadow_root.clone()
+            .filter(|shadow_root| matches!(shadow_root.data.borrow().kind, Kind::ShadowRoot { open: true }))
+    }
+
+    pub fn host(&self) -> Option<MemoryNode> {
+        self.data.borrow().host.upgrade().map(|data| MemoryNode { data })
+    }
+
+    // Crosses from a shadow root to its host, the way composed events and focus do.
+    fn composed_parent(&self) -> Option<MemoryNode> {
+        self.parent().or_else(|| self.host())
+    }
+
+    pub fn children(&self) -> Vec<MemoryNode> {
+        self.data.borrow().children.clone()
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.data.borrow().listeners.len()
+    }
+
+    pub fn is_same(&self, other: &MemoryNode) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+
+    pub fn dispatch(&self, event: &str) -> MemoryEvent {
+        let event = MemoryEvent::new(event, self, true);
+        event.dispatch();
+        event
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::new();
+        self.write_html(&mut html);
+        html
+    }
+
+    fn write_html(&self, html: &mut String) {
+        let data = self.data.borrow();
+        match &data.kind {
+            Kind::Element { name, attributes, .. } => {
+                html.push('<');
+                html.push_str(name);
+                attributes.iter().for_each(|(name, value)| {
+                    html.push_str(format!(" {}=\"{}\"", name, escape(value).replace('"', "&quot;")).as_str());
+                });
+                html.push('>');
+                data.children.iter().for_each(|child| child.write_html(html));
+                html.push_str(format!("</{}>", name).as_str());
+            }
+            Kind::Text { value } => {
+                html.push_str(escape(value).as_str());
+            }
+            Kind::ShadowRoot { .. } => {
+                data.children.iter().for_each(|child| child.write_html(html));
+            }
+        }
+    }
+
+    fn index_of(&self, child: &MemoryNode) -> Option<usize> {
+        self.data.borrow().children.iter().position(|node| node.is_same(child))
+    }
+
+    fn detach(&self) {
+        if let Some(parent) = self.parent() {
+            parent.data.borrow_mut().children.retain(|node| !node.is_same(self));
+        }
+        self.data.borrow_mut().parent = Weak::new();
+    }
+
+    fn reset_scroll(&self) {
+        self.data.borrow_mut().scroll = (0, 0);
+        self.children().iter().for_each(MemoryNode::reset_scroll);
+    }
+
+    fn is_inclusive_ancestor_of(&self, node: &MemoryNode) -> bool {
+        let mut current = Some(node.clone());
+        while let Some(node) = current {
+            if node.is_same(self) {
+                return true
+            }
+            current = node.composed_parent();
+        }
+        false
+    }
+
+    // The node itself, or the host of the outermost shadow root it is in, as the document sees it.
+    fn retarget(&self, scope: Option<&MemoryNode>) -> Option<MemoryNode> {
+        let mut target = self.clone();
+        let mut current = Some(self.clone());
+        while let Some(node) = current {
+            if scope.is_some_and(|scope| scope.is_same(&node)) {
+                return Some(target)
+            }
+            current = node.parent();
+            if current.is_none() {
+                current = node.host();
+                if let Some(host) = &current {
+                    target = host.clone();
+                }
+            }
+        }
+        scope.is_none().then_some(target)
+    }
+
+    fn callbacks(&self, event: &str, capture: bool) -> Vec<Callback<MemoryEvent>> {
+        self.data.borrow().listeners.iter()
+            .filter(|listener| listener.event == event && listener.capture == capture)
+            .map(|listener| listener.callback.clone())
+            .collect()
+    }
+}
+
+impl Debug for MemoryNode {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(formatter, "{}", self.to_html())
+    }
+}
+
+pub struct MemoryEvent {
+    event: String,
+    target: MemoryNode,
+    bubbles: bool,
+    stopped: Cell<bool>,
+}
+
+impl MemoryEvent {
+
+    pub fn new(event: &str, target: &MemoryNode, bubbles: bool) -> MemoryEvent {
+        MemoryEvent {
+            event: String::from(event),
+            target: target.clone(),
+            bubbles,
+            stopped: Cell::new(false),
+        }
+    }
+
+    pub fn event(&self) -> &str {
+        self.event.as_str()
+    }
+
+    pub fn target(&self) -> &MemoryNode {
+        &self.target
+    }
+
+    pub fn bubbles(&self) -> bool {
+        self.bubbles
+    }
+
+    pub fn stop_propagation(&self) {
+        self.stopped.set(true)
+    }
+
+    pub fn propagation_stopped(&self) -> bool {
+        self.stopped.get()
+    }
+
+    // Runs the capture phase from the outermost ancestor down to the target, then the
+    // bubble phase back up, stopping at the first node after which propagation was stopped.
+    pub fn dispatch(&self) {
+        // Events are composed, they leave shadow roots through the host, though without retargeting.
+        let mut path = vec![self.target.clone()];
+        while let Some(parent) = path.last().and_then(|node| node.composed_parent()) {
+            path.push(parent);
+        }
+        for node in path.iter().rev() {
+            node.callbacks(self.event(), true).iter().for_each(|callback| callback(self));
+            if self.propagation_stopped() {
+                return
+            }
+        }
+        for (index, node) in path.iter().enumerate() {
+            if index > 0 && !self.bubbles {
+                return
+            }
+            node.callbacks(self.event(), false).iter().for_each(|callback| callback(self));
+            if self.propagation_stopped() {
+                return
+            }
+        }
+    }
+}
+
+pub struct MemorySubscription {
+    node: Weak<RefCell<NodeData>>,
+    id: usize,
+}
+
+impl Drop for MemorySubscription {
+
+    fn drop(&mut self) {
+        if let Some(data) = self.node.upgrade() {
+            data.borrow_mut().listeners.retain(|listener| listener.id != self.id);
+        }
+    }
+}
+
+impl Backend for MemoryBackend {
+
+    type Node = MemoryNode;
+    type Event = MemoryEvent;
+    type Subscription = MemorySubscription;
+
+    fn create_element(&self, name: &str) -> Result<MemoryNode, Cause> {
+        if !is_valid_name(name) {
+            return Err(invalid_character(name))
+        }
+        Ok(MemoryNode::element(name))
+    }
+
+    fn create_text(&self, value: &str) -> Result<MemoryNode, Cause> {
+        Ok(MemoryNode::text(value))
+    }
+
+    fn tag_name(&self, node: &MemoryNode) -> Option<String> {
+        node.name()
+    }
+
+    fn text(&self, node: &MemoryNode) -> Option<String> {
+        node.value()
+    }
+
+    fn set_text(&self, node: &MemoryNode, value: &str) {
+        if let Kind::Text { value: existing } = &mut node.data.borrow_mut().kind {
+            *existing = String::from(value);
+        }
+    }
+
+    fn attribute_names(&self, node: &MemoryNode) -> Vec<String> {
+        match &node.data.borrow().kind {
+            Kind::Element { attributes, .. } => attributes.iter().map(|(name, _)| name.clone()).collect(),
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => Vec::new(),
+        }
+    }
+
+    fn get_attribute(&self, node: &MemoryNode, name: &str) -> Option<String> {
+        node.attribute(name)
+    }
+
+    fn set_attribute(&self, node: &MemoryNode, name: &str, value: &str) -> Result<(), Cause> {
+        if !is_valid_name(name) {
+            return Err(invalid_character(name))
+        }
+        match &mut node.data.borrow_mut().kind {
+            Kind::Element { attributes, .. } => {
+                match attributes.iter_mut().find(|(attribute, _)| attribute == name) {
+                    Some((_, existing)) => *existing = String::from(value),
+                    None => attributes.push((String::from(name), String::from(value))),
+                }
+                Ok(())
+            }
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => Err(Cause::Message(String::from("not an element"))),
+        }
+    }
+
+    fn remove_attribute(&self, node: &MemoryNode, name: &str) -> Result<(), Cause> {
+        match &mut node.data.borrow_mut().kind {
+            Kind::Element { attributes, .. } => {
+                attributes.retain(|(attribute, _)| attribute != name);
+                Ok(())
+            }
+            Kind::Text { .. } | Kind::ShadowRoot { .. } => Err(Cause::Message(String::from("not an element"))),
+        }
+    }
+
+    fn set_property(&self, node: &MemoryNode, name: &str, value: Property) -> Result<(), Cause> {
+        // Like in a browser, a select only takes the value of one of its options and is left without one otherwise.
+        let value = match (&value, name, node.name().as_deref()) {
+            (Property::Text(text), "value", Some("select")) if !has_option(node, text) => Property::Text(String::new()),
+            _ => value,
+        };
+        // Assigning a different value moves the caret to its end.
+        if let (Property::Text(text), "value") = (&value, name) {
+            if node.property(name).as_ref() != Some(&value) {
+                let end = text.chars().count() as u32;
+                node.data.borrow_mut().selection = Some(Selection { start: end, end, direction: None });
+            }
+        }
+        node.set_property(name, value);
+        Ok(())
+    }
+
+    fn parent(&self, node: &MemoryNode) -> Option<MemoryNode> {
+        node.parent()
+    }
+
+    fn first_child(&self, node: &MemoryNode) -> Option<MemoryNode> {
+        node.data.borrow().children.first().cloned()
+    }
+
+    fn child_at(&self, node: &MemoryNode, index: usize) -> Option<MemoryNode> {
+        node.data.borrow().children.get(index).cloned()
+    }
+
+    fn insert_before(&self, parent: &MemoryNode, node: &MemoryNode, before: Option<&MemoryNode>) -> Result<(), Cause> {
+        if parent.value().is_some() || node.is_inclusive_ancestor_of(parent) {
+            return Err(Cause::Message(String::from("HierarchyRequestError")))
+        }
+        if before.is_some_and(|before| before.is_same(node)) {
+            return Ok(())
+        }
+        if before.is_some_and(|before| parent.index_of(before).is_none()) {
+            return Err(Cause::Message(String::from("NotFoundError")))
+        }
+        if node.parent().is_some() {
+            node.detach();
+            self.detached(node);
+        }
+        let index = before.and_then(|before| parent.index_of(before));
+        let mut data = parent.data.borrow_mut();
+        match index {
+            Some(index) => data.children.insert(index, node.clone()),
+            None => data.children.push(node.clone()),
+        }
+        node.data.borrow_mut().parent = Rc::downgrade(&parent.data);
+        Ok(())
+    }
+
+    fn remove_child(&self, parent: &MemoryNode, node: &MemoryNode) -> Result<(), Cause> {
+        if parent.index_of(node).is_none() {
+            return Err(Cause::Message(String::from("NotFoundError")))
+        }
+        node.detach();
+        self.detached(node);
+        Ok(())
+    }
+
+    fn replace_child(&self, parent: &MemoryNode, node: &MemoryNode, old: &MemoryNode) -> Result<(), Cause> {
+        self.insert_before(parent, node, Some(old))?;
+        self.remove_child(parent, old)
+    }
+
+    fn is_same(&self, node: &MemoryNode, other: &MemoryNode) -> bool {
+        node.is_same(other)
+    }
+
+    fn set_ref(&self, node: &MemoryNode, node_ref: &VRef) -> Result<(), Cause> {
+        node.data.borrow_mut().vref = Some(*node_ref);
+        Ok(())
+    }
+
+    fn get_ref(&self, node: &MemoryNode) -> Option<VRef> {
+        node.data.borrow().vref
+    }
+
+    fn active_element(&self) -> Option<MemoryNode> {
+        self.active.borrow().as_ref().and_then(|active| active.retarget(self.shadow_root.as_ref()))
+    }
+
+    fn focus(&self, node: &MemoryNode) {
+        if node.name().is_some() {
+            self.active.replace(Some(node.clone()));
+        }
+    }
+
+    fn selection(&self, node: &MemoryNode) -> Option<Selection> {
+        node.data.borrow().selection.clone()
+    }
+
+    fn set_selection(&self, node: &MemoryNode, selection: &Selection) {
+        node.data.borrow_mut().selection = Some(selection.clone());
+    }
+
+    fn scroll(&self, node: &MemoryNode) -> (i32, i32) {
+        node.data.borrow().scroll
+    }
+
+    fn set_scroll(&self, node: &MemoryNode, offsets: (i32, i32)) {
+        node.data.borrow_mut().scroll = offsets;
+    }
+
+    fn height(&self, node: &MemoryNode) -> f64 {
+        node.data.borrow().height
+    }
+
+    fn listen(&self, node: &MemoryNode, event: &str, capture: bool, callback: Callback<MemoryEvent>) -> Result<MemorySubscription, Cause> {
+        let mut data = node.data.borrow_mut();
+        let id = data.next_listener;
+        data.next_listener += 1;
+        data.listeners.push(MemoryListener { id, event: String::from(event), capture, callback });
+        Ok(MemorySubscription { node: Rc::downgrade(&node.data), id })
+    }
+
+    fn event_type(&self, event: &MemoryEvent) -> String {
+        String::from(event.event())
+    }
+
+    fn event_target(&self, event: &MemoryEvent) -> Option<MemoryNode> {
+        Some(event.target().clone())
+    }
+
+    fn event_bubbles(&self, event: &MemoryEvent) -> bool {
+        event.bubbles()
+    }
+
+    fn propagation_stopped(&self, event: &MemoryEvent) -> bool {
+        event.propagation_stopped()
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || ['<', '>', '"', '\'', '/', '='].contains(&c))
+}
+
+fn invalid_character(name: &str) -> Cause {
+    Cause::Message(format!("InvalidCharacterError: {:?}", name))
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn has_option(select: &MemoryNode, value: &str) -> bool {
+    select.children().iter()
+        .filter(|option| option.name().as_deref() == Some("option"))
+        .any(|option| {
+            let text: String = option.children().iter().filter_map(MemoryNode::value).collect();
+            option.attribute("value").unwrap_or(text) == value
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use crate::{Backend, MemoryBackend, MemoryEvent, MemoryNode, Property, Selection};
+
+    #[test]
+    fn test_insert_and_remove() {
+        let backend = MemoryBackend::new();
+        let list = MemoryNode::element("ul");
+        let first = MemoryNode::element("li");
+        let second = MemoryNode::element("li");
+        let text = MemoryNode::text("a < b");
+
+        backend.insert_before(&list, &second, None).expect("inserted");
+        backend.insert_before(&list, &first, Some(&second)).expect("inserted");
+        backend.insert_before(&first, &text, None).expect("inserted");
+        backend.set_attribute(&second, "class", "last").expect("attribute set");
+
+        assert_that!(list.to_html()).is_equal_to(String::from("<ul><li>a &lt; b</li><li class=\"last\"></li></ul>"));
+
+        backend.insert_before(&list, &second, Some(&first)).expect("moved");
+        backend.remove_child(&list, &first).expect("removed");
+
+        assert_that!(list.to_html()).is_equal_to(String::from("<ul><li class=\"last\"></li></ul>"));
+        assert_that!(first.parent()).is_none();
+        assert_that!(backend.remove_child(&list, &first)).is_err();
+        assert_that!(backend.insert_before(&second, &list, None)).is_err();
+        assert_that!(backend.create_element("bad name")).is_err();
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let backend = MemoryBackend::new();
+        let outer = MemoryNode::element("div");
+        let inner = MemoryNode::element("button");
+        backend.insert_before(&outer, &inner, None).expect("inserted");
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let record = |name: &'static str, stop: bool| {
+            let calls = calls.clone();
+            Rc::new(move |event: &MemoryEvent| {
+                calls.borrow_mut().push(name);
+                if stop {
+                    event.stop_propagation()
+                }
+            })
+        };
+        let _capture = backend.listen(&outer, "click", true, record("capture", false)).expect("listening");
+        let _outer = backend.listen(&outer, "click", false, record("outer", false)).expect("listening");
+        let inner_listener = backend.listen(&inner, "click", false, record("inner", true)).expect("listening");
+
+        inner.dispatch("click");
+        drop(inner_listener);
+        inner.dispatch("click");
+        MemoryEvent::new("click", &inner, false).dispatch();
+
+        assert_that!(*calls.borrow()).is_equal_to(vec!["capture", "inner", "capture", "outer", "capture"]);
+        assert_that!(inner.listener_count()).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_detaching_loses_focus_and_scroll() {
+        let backend = MemoryBackend::new();
+        let form = MemoryNode::element("form");
+        let list = MemoryNode::element("ul");
+        let input = MemoryNode::element("input");
+        backend.insert_before(&form, &list, None).expect("inserted");
+        backend.insert_before(&form, &input, None).expect("inserted");
+        backend.focus(&input);
+        backend.set_scroll(&list, (0, 40));
+        backend.set_selection(&input, &Selection { start: 1, end: 2, direction: None });
+
+        backend.set_property(&input, "value", Property::Text(String::from("abc"))).expect("property set");
+
+        assert_that!(backend.selection(&input)).is_equal_to(Some(Selection { start: 3, end: 3, direction: None }));
+
+        backend.insert_before(&form, &input, Some(&list)).expect("moved");
+        backend.remove_child(&form, &list).expect("removed");
+
+        assert_that!(backend.active_element()).is_none();
+        assert_that!(backend.scroll(&list)).is_equal_to((0, 0));
+    }
+}
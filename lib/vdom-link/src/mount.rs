@@ -0,0 +1,669 @@
+use std::collections::{HashMap, HashSet};
+
+use web_sys::Element;
+
+use vdom::{VRef, VTree};
+
+use crate::{Backend, PerformanceClock, VNodeLink, WebBackend};
+use crate::error::{Error, ErrorMode};
+use crate::events::{Bindings, Delegation, EventMode};
+use crate::operations::{Counted, Operations};
+use crate::props;
+use crate::report::{Instrumentation, Recorder};
+use crate::state::Snapshot;
+use crate::transition::{Timing, Transitions};
+
+pub struct Mount<B: Backend = WebBackend> {
+    pub(crate) backend: Counted<B>,
+    pub(crate) container: B::Node,
+    pub(crate) root: Option<VRef>,
+    pub(crate) nodes: HashMap<VRef, B::Node>,
+    pub(crate) listeners: Bindings<Counted<B>>,
+    pub(crate) events: EventMode,
+    pub(crate) errors: ErrorMode,
+    pub(crate) delegation: Delegation<Counted<B>>,
+    pub(crate) cleanups: HashMap<VRef, Vec<Cleanup<B::Node>>>,
+    pub(crate) transitions: Transitions<Counted<B>>,
+    pub(crate) instrumentation: Instrumentation,
+    pub(crate) preserved: Snapshot,
+}
+
+pub type Cleanup<N = web_sys::Node> = Box<dyn FnOnce(&N)>;
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub struct MountOptions {
+    pub events: EventMode,
+    pub errors: ErrorMode,
+}
+
+impl Mount<WebBackend> {
+
+    pub fn new(container: &Element) -> Result<Mount, Error> {
+        Mount::with_options(container, MountOptions::default())
+    }
+
+    pub fn with_options(container: &Element, options: MountOptions) -> Result<Mount, Error> {
+        let backend = WebBackend::for_element(container)?;
+        Ok(Mount::with_web_backend(backend, &container.clone().into(), options))
+    }
+
+    pub(crate) fn with_web_backend(backend: WebBackend, container: &web_sys::Node, options: MountOptions) -> Mount {
+        let mut mount = Mount::with_backend(backend, container, options);
+        if let Some(window) = web_sys::window() {
+            mount.set_timing(Timing::web(&window));
+            if let Some(clock) = PerformanceClock::new(&window) {
+                mount.set_clock(clock);
+            }
+        }
+        mount
+    }
+}
+
+impl<B: Backend> Mount<B> {
+
+    pub fn with_backend(backend: B, container: &B::Node, options: MountOptions) -> Mount<B> {
+        Mount {
+            backend: Counted::new(backend, container),
+            container: container.clone(),
+            root: None,
+            nodes: HashMap::new(),
+            listeners: HashMap::new(),
+            events: options.events,
+            errors: options.errors,
+            delegation: Delegation::default(),
+            cleanups: HashMap::new(),
+            transitions: Transitions::default(),
+            instrumentation: Instrumentation::default(),
+            preserved: Snapshot::default(),
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend.inner
+    }
+
+    pub fn operations(&self) -> Operations {
+        self.backend.operations()
+    }
+
+    pub fn reset_operations(&mut self) {
+        self.backend.reset()
+    }
+
+    pub fn event_mode(&self) -> EventMode {
+        self.events
+    }
+
+    pub fn error_mode(&self) -> ErrorMode {
+        self.errors
+    }
+
+    pub fn container(&self) -> &B::Node {
+        &self.container
+    }
+
+    pub fn root(&self) -> Option<&B::Node> {
+        self.root.as_ref().and_then(|root| self.nodes.get(root))
+    }
+
+    pub fn get(&self, node: &VRef) -> Option<&B::Node> {
+        self.nodes.get(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn add_cleanup<F>(&mut self, node: &VRef, cleanup: F) where F: FnOnce(&B::Node) + 'static {
+        self.cleanups.entry(*node).or_default().push(Box::new(cleanup));
+    }
+
+    pub fn unmount(&mut self) -> Result<(), Error> {
+        self.cancel_transitions();
+        let root = self.root.take().and_then(|root| self.nodes.get(&root).cloned().map(|node| (root, node)));
+        let node_refs: Vec<VRef> = self.nodes.keys().cloned().collect();
+        node_refs.iter().for_each(|node_ref| {
+            self.release(node_ref);
+            self.forget_transition(node_ref);
+        });
+        self.listeners.clear();
+        self.delegation = Delegation::default();
+        self.cleanups.clear();
+        if let Some((root_ref, root)) = root {
+            self.errors.check(detach(&self.backend, &root_ref, &root))?;
+        }
+        Ok(())
+    }
+
+    pub fn update(&mut self, tree: &VTree) -> Result<(), Error> {
+        let mut recorder = self.record("update");
+        self.snapshot();
+        recorder.lap("snapshot");
+        let result = self.reconcile(tree, &mut recorder);
+        self.reapply_transitions();
+        self.restore();
+        recorder.lap("restore");
+        self.finish(recorder);
+        result
+    }
+
+    fn reconcile(&mut self, tree: &VTree, recorder: &mut Recorder) -> Result<(), Error> {
+        // Nodes taken out with remove_child are still part of the tree, but no longer rendered.
+        let reachable = reachable(tree);
+        let stale: Vec<VRef> = self.nodes.keys()
+            .filter(|node_ref| !reachable.contains(node_ref))
+            .cloned()
+            .collect();
+        let mut removed: Vec<(VRef, B::Node)> = Vec::new();
+        for node_ref in stale.iter() {
+            if let Some(node) = self.release(node_ref) {
+                if !self.leave(node_ref, &node) {
+                    removed.push((*node_ref, node));
+                }
+            }
+        }
+        // Removed subtrees are detached top down, so their descendants go along instead of one by one.
+        // Descendants of leaving nodes go once their ancestor is done.
+        removed.sort_by_cached_key(|(_, node)| self.depth(node));
+        for (node_ref, node) in removed {
+            if self.depth(&node).is_some() && !self.is_inside_leaving_node(&node) {
+                self.errors.check(detach(&self.backend, &node_ref, &node))?;
+            }
+        }
+        recorder.lap("release");
+
+        self.root = tree.get_root();
+        if let Some(root) = tree.get_root() {
+            if let Some(node) = self.render(tree, &root)? {
+                let parent = self.backend.parent(&node);
+                if !parent.is_some_and(|parent| self.backend.is_same(&parent, &self.container)) {
+                    let appended = self.backend.insert_before(&self.container, &node, None)
+                        .map_err(|cause| Error::dom(&root, "append root", cause));
+                    self.errors.check(appended)?;
+                }
+            }
+        }
+        recorder.lap("render");
+        Ok(())
+    }
+
+    // The number of ancestors up to the container, if the node is below it.
+    fn depth(&self, node: &B::Node) -> Option<usize> {
+        let mut depth = 0;
+        let mut current = self.backend.parent(node);
+        while let Some(parent) = current {
+            if self.backend.is_same(&parent, &self.container) {
+                return Some(depth)
+            }
+            depth += 1;
+            current = self.backend.parent(&parent);
+        }
+        None
+    }
+
+    // Forgets a node, its listeners and runs its cleanup hooks, leaving the DOM untouched.
+    pub(crate) fn release(&mut self, node_ref: &VRef) -> Option<B::Node> {
+        let node = self.nodes.remove(node_ref);
+        self.unbind(node_ref);
+        if let (Some(node), Some(cleanups)) = (&node, self.cleanups.remove(node_ref)) {
+            cleanups.into_iter().for_each(|cleanup| cleanup(node));
+        }
+        node
+    }
+
+    pub(crate) fn render(&mut self, tree: &VTree, node_ref: &VRef) -> Result<Option<B::Node>, Error> {
+        let mut leftovers = Vec::new();
+        let node = self.render_node(tree, node_ref, &mut leftovers)?;
+        // Children moved to a node rendered later have been taken out by now, the others do not belong there anymore.
+        for (parent_ref, parent, child) in leftovers {
+            if self.backend.parent(&child).is_some_and(|current| self.backend.is_same(&current, &parent)) {
+                self.errors.check(detach(&self.backend, &parent_ref, &child))?;
+            }
+        }
+        Ok(node)
+    }
+
+    fn render_node(&mut self, tree: &VTree, node_ref: &VRef, leftovers: &mut Vec<(VRef, B::Node, B::Node)>) -> Result<Option<B::Node>, Error> {
+        let vnode = match tree.get_node(node_ref) {
+            Some(vnode) => vnode,
+            None => return Ok(None),
+        };
+
+        let existing = self.nodes.get(node_ref).cloned();
+        if existing.is_none() {
+            self.cancel_leave(node_ref);
+        }
+        let before = self.backend.operations();
+        let unsettled = vnode.item.as_ref().is_some_and(|item| props::unsettled(&self.backend, existing.as_ref(), item));
+        let node = match self.errors.check(vnode.upsert(&self.backend, existing.as_ref(), self.errors))? {
+            Some(node) => node,
+            None => return Ok(None),
+        };
+        let created = match existing {
+            Some(existing) if self.backend.is_same(&existing, &node) => {
+                self.track_update(node_ref, before);
+                false
+            }
+            Some(existing) => {
+                self.preserve(&existing);
+                self.errors.check(detach(&self.backend, node_ref, &existing))?;
+                self.enter(node_ref, &node);
+                true
+            }
+            None => {
+                self.enter(node_ref, &node);
+                true
+            }
+        };
+
+        let mut index = 0;
+        for child in tree.children(node_ref).iter() {
+            if let Some(child_node) = self.render_node(tree, &child.id, leftovers)? {
+                let mut current = self.backend.child_at(&node, index);
+                // Leaving nodes keep their place until their transition is done.
+                while current.as_ref().is_some_and(|current| self.is_leaving_node(current)) {
+                    index += 1;
+                    current = self.backend.child_at(&node, index);
+                }
+                if !current.as_ref().is_some_and(|current| self.backend.is_same(&child_node, current)) {
+                    if self.backend.parent(&child_node).is_some() {
+                        self.preserve(&child_node);
+                    }
+                    let inserted = self.backend.insert_before(&node, &child_node, current.as_ref())
+                        .map_err(|cause| Error::dom(&child.id, "insert child", cause));
+                    if self.errors.check(inserted)?.is_none() {
+                        continue
+                    }
+                }
+                index += 1;
+            }
+        }
+        while let Some(child) = self.backend.child_at(&node, index) {
+            if !self.is_leaving_node(&child) {
+                leftovers.push((*node_ref, node.clone(), child));
+            }
+            index += 1;
+        }
+
+        if unsettled {
+            let settled = props::settle(&self.backend, &node)
+                .map_err(|cause| Error::attribute(node_ref, "set property", "value", cause));
+            self.errors.check(settled)?;
+        }
+        if let Some(item) = &vnode.item {
+            self.bind(node_ref, &node, item.listeners())?;
+        }
+        // Kept nodes are tagged already, re-rendering an unchanged tree writes nothing.
+        if created {
+            self.track(node_ref, node.clone());
+        }
+        Ok(Some(node))
+    }
+}
+
+pub fn mount(tree: &VTree, container: &Element) -> Result<Mount, Error> {
+    mount_with_options(tree, container, MountOptions::default())
+}
+
+pub fn mount_with_options(tree: &VTree, container: &Element, options: MountOptions) -> Result<Mount, Error> {
+    let mut mount = Mount::with_options(container, options)?;
+    mount.update(tree)?;
+    Ok(mount)
+}
+
+fn reachable(tree: &VTree) -> HashSet<VRef> {
+    let mut reachable = HashSet::new();
+    let mut pending: Vec<VRef> = tree.get_root().into_iter().collect();
+    while let Some(node_ref) = pending.pop() {
+        if reachable.insert(node_ref) {
+            pending.extend(tree.children(&node_ref).iter().map(|child| child.id));
+        }
+    }
+    reachable
+}
+
+pub(crate) fn detach<B: Backend>(backend: &B, node_ref: &VRef, node: &B::Node) -> Result<(), Error> {
+    if let Some(parent) = backend.parent(node) {
+        backend.remove_child(&parent, node).map_err(|cause| Error::dom(node_ref, "remove node", cause))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{Listener, VItem, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::{Backend, Error, ErrorMode, EventMode, MountOptions, Operations};
+    use crate::testing::{html, memory_mount};
+
+    #[test]
+    fn test_update() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "container")]);
+        let title = element(&mut tree, Some(&div), "h1", &[]);
+        let label = text(&mut tree, &title, "Hello");
+        let mut mount = memory_mount(MountOptions::default());
+
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div class=\"container\"><h1>Hello</h1></div>"));
+        assert_that!(mount.len()).is_equal_to(3);
+        let mounted_title = mount.get(&title).cloned().expect("title mounted");
+
+        tree.update_node(&label, Box::new(|node| node.item = Some(VItem::Text { value: String::from("World") })));
+        if let Some(item) = tree.item_mut(&div) {
+            item.set_attribute("class", "box");
+        }
+        let paragraph = element(&mut tree, Some(&div), "p", &[]);
+        tree.insert_before(&div, &paragraph, &title);
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div class=\"box\"><p></p><h1>World</h1></div>"));
+        assert_that!(mount.get(&title).is_some_and(|node| node.is_same(&mounted_title))).is_true();
+
+        tree.remove_node(&title);
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div class=\"box\"><p></p></div>"));
+        assert_that!(mount.len()).is_equal_to(2);
+    }
+
+    #[test]
+    fn test_mount_whole_tree() {
+        let mut tree = VTree::new();
+        let nav = element(&mut tree, None, "nav", &[]);
+        let list = element(&mut tree, Some(&nav), "ul", &[]);
+        let items: Vec<_> = ["Home", "About", "Contact"].iter()
+            .map(|label| {
+                let item = element(&mut tree, Some(&list), "li", &[]);
+                let link = element(&mut tree, Some(&item), "a", &[("href", "#")]);
+                text(&mut tree, &link, label);
+                item
+            })
+            .collect();
+        let footer = element(&mut tree, Some(&nav), "small", &[]);
+        text(&mut tree, &footer, "v1");
+        let mut mount = memory_mount(MountOptions::default());
+
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(html(&mount)).is_equal_to(String::from(concat!(
+            "<nav><ul><li><a href=\"#\">Home</a></li><li><a href=\"#\">About</a></li>",
+            "<li><a href=\"#\">Contact</a></li></ul><small>v1</small></nav>",
+        )));
+        assert_that!(mount.container().children()).has_length(1);
+        assert_that!(mount.root().is_some_and(|root| root.is_same(&mount.container().children()[0]))).is_true();
+        assert_that!(mount.len()).is_equal_to(tree.nodes().len());
+        assert_that!(items.iter().all(|item| mount.get(item).is_some_and(|node| node.name() == Some(String::from("li"))))).is_true();
+    }
+
+    #[test]
+    fn test_text_nodes() {
+        let mut tree = VTree::new();
+        let paragraph = element(&mut tree, None, "p", &[]);
+        let greeting = text(&mut tree, &paragraph, "Hello ");
+        let name = element(&mut tree, Some(&paragraph), "b", &[]);
+        text(&mut tree, &name, "Jane");
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let greeting_node = mount.get(&greeting).cloned().expect("text mounted");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<p>Hello <b>Jane</b></p>"));
+        assert_that!(mount.backend().tag_name(&greeting_node)).is_none();
+        assert_that!(mount.backend().text(&greeting_node)).is_equal_to(Some(String::from("Hello ")));
+
+        tree.update_node(&greeting, Box::new(|node| node.item = Some(VItem::Text { value: String::from("Bye ") })));
+        mount.reset_operations();
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<p>Bye <b>Jane</b></p>"));
+        assert_that!(mount.get(&greeting).is_some_and(|node| node.is_same(&greeting_node))).is_true();
+        assert_that!(mount.operations()).is_equal_to(Operations { texts: 1, ..Operations::default() });
+    }
+
+    #[test]
+    fn test_user_ids_survive() {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[("id", "login")]);
+        let input = element(&mut tree, Some(&form), "input", &[("id", "user")]);
+        let mut first = memory_mount(MountOptions::default());
+        let mut second = memory_mount(MountOptions::default());
+        first.update(&tree).expect("mounted");
+        second.update(&tree).expect("mounted");
+
+        assert_that!(html(&first)).is_equal_to(String::from("<form id=\"login\"><input id=\"user\"></input></form>"));
+        assert_that!(html(&second)).is_equal_to(html(&first));
+        assert_that!(first.backend().get_ref(first.get(&input).expect("input mounted"))).is_equal_to(Some(input));
+
+        if let Some(item) = tree.item_mut(&input) {
+            item.set_attribute("class", "wide");
+        }
+        first.update(&tree).expect("updated");
+
+        assert_that!(html(&first)).is_equal_to(String::from("<form id=\"login\"><input id=\"user\" class=\"wide\"></input></form>"));
+        assert_that!(html(&second)).is_equal_to(String::from("<form id=\"login\"><input id=\"user\"></input></form>"));
+        assert_that!(first.get(&input).is_some_and(|node| !node.is_same(second.get(&input).expect("input mounted")))).is_true();
+    }
+
+    #[test]
+    fn test_update_attributes() {
+        let mut tree = VTree::new();
+        let link = element(&mut tree, None, "a", &[("href", "/home"), ("class", "nav"), ("title", "Home")]);
+        text(&mut tree, &link, "Home");
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+        let link_node = mount.get(&link).cloned().expect("link mounted");
+
+        if let Some(item) = tree.item_mut(&link) {
+            item.set_attribute("class", "nav active");
+            item.remove_attribute("title");
+            item.set_attribute("target", "_self");
+        }
+        mount.reset_operations();
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<a href=\"/home\" class=\"nav active\" target=\"_self\">Home</a>"));
+        assert_that!(mount.get(&link).is_some_and(|node| node.is_same(&link_node))).is_true();
+        assert_that!(mount.operations()).is_equal_to(Operations { attributes: 3, ..Operations::default() });
+    }
+
+    #[test]
+    fn test_remove_child() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        let first = element(&mut tree, Some(&div), "section", &[]);
+        let second = element(&mut tree, Some(&div), "section", &[]);
+        let paragraph = element(&mut tree, Some(&first), "p", &[]);
+        text(&mut tree, &paragraph, "x");
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        tree.remove_child(&first, &paragraph);
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div><section></section><section></section></div>"));
+        assert_that!(mount.len()).is_equal_to(3);
+
+        tree.append_child(&first, &paragraph);
+        mount.update(&tree).expect("updated");
+        let mounted = mount.get(&paragraph).cloned().expect("paragraph mounted");
+        tree.remove_child(&first, &paragraph);
+        tree.append_child(&second, &paragraph);
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div><section></section><section><p>x</p></section></div>"));
+        assert_that!(mount.get(&paragraph).is_some_and(|node| node.is_same(&mounted))).is_true();
+
+        let extra = mount.backend().create_element("span").expect("created");
+        mount.backend().insert_before(mount.get(&second).expect("section mounted"), &extra, None).expect("inserted");
+        mount.update(&tree).expect("updated");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<div><section></section><section><p>x</p></section></div>"));
+    }
+
+    #[test]
+    fn test_batched_mount() {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[("class", "list")]);
+        (0..10).for_each(|index| {
+            let item = element(&mut tree, Some(&list), "li", &[]);
+            text(&mut tree, &item, format!("Item {}", index).as_str());
+        });
+        let mut mount = memory_mount(MountOptions::default());
+
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(mount.operations()).is_equal_to(Operations {
+            created: 21,
+            inserted: 20,
+            attached: 1,
+            attributes: 1,
+            refs: 21,
+            ..Operations::default()
+        });
+
+        mount.reset_operations();
+        mount.update(&tree).expect("rendered again");
+
+        assert_that!(mount.operations()).is_equal_to(Operations::default());
+
+        mount.reset_operations();
+        let item = element(&mut tree, Some(&list), "li", &[]);
+        text(&mut tree, &item, "Item 10");
+        mount.update(&tree).expect("updated");
+
+        assert_that!(mount.operations()).is_equal_to(Operations {
+            created: 2,
+            inserted: 1,
+            attached: 1,
+            refs: 2,
+            ..Operations::default()
+        });
+
+        mount.reset_operations();
+        let nested = element(&mut tree, Some(&item), "span", &[]);
+        text(&mut tree, &nested, "new");
+        mount.update(&tree).expect("updated");
+        mount.reset_operations();
+        tree.remove_node(&item);
+        mount.update(&tree).expect("updated");
+
+        assert_that!(mount.operations()).is_equal_to(Operations {
+            removed: 1,
+            ..Operations::default()
+        });
+        assert_that!(mount.len()).is_equal_to(21);
+    }
+
+    #[test]
+    fn test_unmount() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        let button = element(&mut tree, Some(&div), "button", &[]);
+        let cleaned = Rc::new(RefCell::new(Vec::new()));
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        [div, button].iter().for_each(|node| {
+            let cleaned = cleaned.clone();
+            mount.add_cleanup(node, move |node| cleaned.borrow_mut().push(node.name()));
+        });
+        mount.unmount().expect("unmounted");
+
+        assert_that!(html(&mount)).is_equal_to(String::new());
+        assert_that!(mount.is_empty()).is_true();
+        assert_that!(mount.root()).is_none();
+        assert_that!(cleaned.borrow().len()).is_equal_to(2);
+    }
+
+    #[test]
+    fn test_unmount_drops_listeners() {
+        let token = Rc::new(());
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        let button = element(&mut tree, Some(&div), "button", &[]);
+        if let Some(item) = tree.item_mut(&button) {
+            let token = token.clone();
+            item.add_listener(Listener::new("click", move |_| drop(token.clone())));
+        }
+        let mut direct = memory_mount(MountOptions::default());
+        let mut delegated = memory_mount(MountOptions { events: EventMode::Delegated, ..MountOptions::default() });
+        direct.update(&tree).expect("mounted");
+        delegated.update(&tree).expect("mounted");
+        let button_node = direct.get(&button).cloned().expect("button mounted");
+        drop(tree);
+
+        direct.unmount().expect("unmounted");
+        delegated.unmount().expect("unmounted");
+
+        assert_that!(button_node.listener_count()).is_equal_to(0);
+        assert_that!(delegated.container().listener_count()).is_equal_to(0);
+        assert_that!(Rc::strong_count(&token)).is_equal_to(1);
+        assert_that!(direct.container().children()).is_empty();
+        assert_that!(delegated.container().children()).is_empty();
+        assert_that!(direct.get(&div)).is_none();
+        assert_that!(delegated.is_empty()).is_true();
+    }
+
+    #[test]
+    fn test_error_modes() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("bad name", "1"), ("class", "box")]);
+        element(&mut tree, Some(&div), "bad tag", &[]);
+        element(&mut tree, Some(&div), "p", &[]);
+
+        let mut strict = memory_mount(MountOptions::default());
+        let error = strict.update(&tree).expect_err("strict mode fails");
+
+        assert_that!(error.node()).is_equal_to(Some(&div));
+        assert_that!(error.to_string()).starts_with("Failed to set attribute 'bad name'");
+
+        let mut lenient = memory_mount(MountOptions { errors: ErrorMode::Lenient, ..MountOptions::default() });
+        lenient.update(&tree).expect("lenient mode skips failures");
+
+        assert_that!(html(&lenient)).is_equal_to(String::from("<div class=\"box\"><p></p></div>"));
+    }
+
+    #[test]
+    fn test_errors_name_node_and_attribute() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "box")]);
+        let input = element(&mut tree, Some(&div), "input", &[("bad name", "1")]);
+        let mut strict = memory_mount(MountOptions::default());
+
+        match strict.update(&tree).expect_err("bad attribute fails") {
+            Error::Dom { node, operation, attribute, .. } => {
+                assert_that!(node).is_equal_to(Some(input));
+                assert_that!(operation).is_equal_to("set attribute");
+                assert_that!(attribute).is_equal_to(Some(String::from("bad name")));
+            }
+            error => panic!("Unexpected error {}", error),
+        }
+
+        if let Some(item) = tree.item_mut(&input) {
+            item.remove_attribute("bad name");
+        }
+        let empty = tree.create_random_node();
+        tree.append_child(&div, &empty);
+        let mut strict = memory_mount(MountOptions::default());
+
+        assert_that!(strict.update(&tree)).is_equal_to(Err(Error::MissingItem { node: empty }));
+
+        let mut lenient = memory_mount(MountOptions { errors: ErrorMode::Lenient, ..MountOptions::default() });
+        lenient.update(&tree).expect("lenient mode skips the node");
+
+        assert_that!(html(&lenient)).is_equal_to(String::from("<div class=\"box\"><input></input></div>"));
+        assert_that!(lenient.get(&empty)).is_none();
+    }
+}
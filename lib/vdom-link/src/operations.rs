@@ -0,0 +1,248 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use vdom::VRef;
+
+use crate::{Backend, Callback, Cause, Property, Selection};
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub struct Operations {
+    pub created: usize,
+    // Insertions into nodes which are not part of the document yet.
+    pub inserted: usize,
+    // Insertions into the container or nodes below it, each of which may cause a reflow.
+    pub attached: usize,
+    pub removed: usize,
+    pub attributes: usize,
+    pub properties: usize,
+    pub texts: usize,
+    pub listeners: usize,
+    // Tags linking new nodes back to their VRef.
+    pub refs: usize,
+}
+
+impl Operations {
+
+    pub fn total(&self) -> usize {
+        self.created + self.inserted + self.attached + self.removed + self.attributes + self.properties + self.texts + self.listeners + self.refs
+    }
+
+    // The operations performed since the earlier count was taken.
+    pub fn since(&self, earlier: Operations) -> Operations {
+        Operations {
+            created: self.created.saturating_sub(earlier.created),
+            inserted: self.inserted.saturating_sub(earlier.inserted),
+            attached: self.attached.saturating_sub(earlier.attached),
+            removed: self.removed.saturating_sub(earlier.removed),
+            attributes: self.attributes.saturating_sub(earlier.attributes),
+            properties: self.properties.saturating_sub(earlier.properties),
+            texts: self.texts.saturating_sub(earlier.texts),
+            listeners: self.listeners.saturating_sub(earlier.listeners),
+            refs: self.refs.saturating_sub(earlier.refs),
+        }
+    }
+}
+
+// Wraps the backend of a mount to count the operations it performs.
+pub(crate) struct Counted<B: Backend> {
+    pub(crate) inner: B,
+    container: B::Node,
+    operations: Rc<Cell<Operations>>,
+}
+
+impl<B: Backend> Clone for Counted<B> {
+
+    fn clone(&self) -> Self {
+        Counted {
+            inner: self.inner.clone(),
+            container: self.container.clone(),
+            operations: self.operations.clone(),
+        }
+    }
+}
+
+impl<B: Backend> Counted<B> {
+
+    pub(crate) fn new(inner: B, container: &B::Node) -> Counted<B> {
+        Counted {
+            inner,
+            container: container.clone(),
+            operations: Rc::new(Cell::new(Operations::default())),
+        }
+    }
+
+    pub(crate) fn operations(&self) -> Operations {
+        self.operations.get()
+    }
+
+    pub(crate) fn reset(&self) {
+        self.operations.set(Operations::default())
+    }
+
+    fn count<F>(&self, update: F) where F: FnOnce(&mut Operations) {
+        let mut operations = self.operations.get();
+        update(&mut operations);
+        self.operations.set(operations);
+    }
+
+    fn count_insertion(&self, parent: &B::Node) {
+        if self.is_attached(parent) {
+            self.count(|operations| operations.attached += 1)
+        } else {
+            self.count(|operations| operations.inserted += 1)
+        }
+    }
+
+    fn is_attached(&self, node: &B::Node) -> bool {
+        let mut current = Some(node.clone());
+        while let Some(node) = current {
+            if self.inner.is_same(&node, &self.container) {
+                return true
+            }
+            current = self.inner.parent(&node);
+        }
+        false
+    }
+}
+
+impl<B: Backend> Backend for Counted<B> {
+
+    type Node = B::Node;
+    type Event = B::Event;
+    type Subscription = B::Subscription;
+
+    fn create_element(&self, name: &str) -> Result<B::Node, Cause> {
+        self.count(|operations| operations.created += 1);
+        self.inner.create_element(name)
+    }
+
+    fn create_text(&self, value: &str) -> Result<B::Node, Cause> {
+        self.count(|operations| operations.created += 1);
+        self.inner.create_text(value)
+    }
+
+    fn tag_name(&self, node: &B::Node) -> Option<String> {
+        self.inner.tag_name(node)
+    }
+
+    fn text(&self, node: &B::Node) -> Option<String> {
+        self.inner.text(node)
+    }
+
+    fn set_text(&self, node: &B::Node, value: &str) {
+        self.count(|operations| operations.texts += 1);
+        self.inner.set_text(node, value)
+    }
+
+    fn attribute_names(&self, node: &B::Node) -> Vec<String> {
+        self.inner.attribute_names(node)
+    }
+
+    fn get_attribute(&self, node: &B::Node, name: &str) -> Option<String> {
+        self.inner.get_attribute(node, name)
+    }
+
+    fn set_attribute(&self, node: &B::Node, name: &str, value: &str) -> Result<(), Cause> {
+        self.count(|operations| operations.attributes += 1);
+        self.inner.set_attribute(node, name, value)
+    }
+
+    fn remove_attribute(&self, node: &B::Node, name: &str) -> Result<(), Cause> {
+        self.count(|operations| operations.attributes += 1);
+        self.inner.remove_attribute(node, name)
+    }
+
+    fn set_property(&self, node: &B::Node, name: &str, value: Property) -> Result<(), Cause> {
+        self.count(|operations| operations.properties += 1);
+        self.inner.set_property(node, name, value)
+    }
+
+    fn parent(&self, node: &B::Node) -> Option<B::Node> {
+        self.inner.parent(node)
+    }
+
+    fn first_child(&self, node: &B::Node) -> Option<B::Node> {
+        self.inner.first_child(node)
+    }
+
+    fn child_at(&self, node: &B::Node, index: usize) -> Option<B::Node> {
+        self.inner.child_at(node, index)
+    }
+
+    fn insert_before(&self, parent: &B::Node, node: &B::Node, before: Option<&B::Node>) -> Result<(), Cause> {
+        self.count_insertion(parent);
+        self.inner.insert_before(parent, node, before)
+    }
+
+    fn remove_child(&self, parent: &B::Node, node: &B::Node) -> Result<(), Cause> {
+        self.count(|operations| operations.removed += 1);
+        self.inner.remove_child(parent, node)
+    }
+
+    fn replace_child(&self, parent: &B::Node, node: &B::Node, old: &B::Node) -> Result<(), Cause> {
+        self.count_insertion(parent);
+        self.inner.replace_child(parent, node, old)
+    }
+
+    fn is_same(&self, node: &B::Node, other: &B::Node) -> bool {
+        self.inner.is_same(node, other)
+    }
+
+    fn set_ref(&self, node: &B::Node, node_ref: &VRef) -> Result<(), Cause> {
+        self.count(|operations| operations.refs += 1);
+        self.inner.set_ref(node, node_ref)
+    }
+
+    fn get_ref(&self, node: &B::Node) -> Option<VRef> {
+        self.inner.get_ref(node)
+    }
+
+    fn active_element(&self) -> Option<B::Node> {
+        self.inner.active_element()
+    }
+
+    fn focus(&self, node: &B::Node) {
+        self.inner.focus(node)
+    }
+
+    fn selection(&self, node: &B::Node) -> Option<Selection> {
+        self.inner.selection(node)
+    }
+
+    fn set_selection(&self, node: &B::Node, selection: &Selection) {
+        self.inner.set_selection(node, selection)
+    }
+
+    fn scroll(&self, node: &B::Node) -> (i32, i32) {
+        self.inner.scroll(node)
+    }
+
+    fn set_scroll(&self, node: &B::Node, offsets: (i32, i32)) {
+        self.inner.set_scroll(node, offsets)
+    }
+
+    fn height(&self, node: &B::Node) -> f64 {
+        self.inner.height(node)
+    }
+
+    fn listen(&self, node: &B::Node, event: &str, capture: bool, callback: Callback<B::Event>) -> Result<B::Subscription, Cause> {
+        self.count(|operations| operations.listeners += 1);
+        self.inner.listen(node, event, capture, callback)
+    }
+
+    fn event_type(&self, event: &B::Event) -> String {
+        self.inner.event_type(event)
+    }
+
+    fn event_target(&self, event: &B::Event) -> Option<B::Node> {
+        self.inner.event_target(event)
+    }
+
+    fn event_bubbles(&self, event: &B::Event) -> bool {
+        self.inner.event_bubbles(event)
+    }
+
+    fn propagation_stopped(&self, event: &B::Event) -> bool {
+        self.inner.propagation_stopped(event)
+    }
+}
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+
+use vdom::{diff_with_stats, Listener, Patch, VItem, VNode, VRef, VTree};
+
+use crate::{Backend, Error, ErrorMode, Mount, VNodeLink};
+use crate::mount::detach;
+use crate::props;
+use crate::report::Recorder;
+
+impl<B: Backend> Mount<B> {
+
+    pub fn apply(&mut self, patches: &[Patch]) -> Result<(), Error> {
+        let recorder = self.record("apply");
+        self.apply_recorded(patches, recorder)
+    }
+
+    // Diffs the trees and applies the result, with the diff as part of the render report.
+    pub fn patch(&mut self, old: &VTree, new: &VTree) -> Result<(), Error> {
+        let mut recorder = self.record("patch");
+        let (patches, stats) = diff_with_stats(old, new);
+        recorder.lap("diff");
+        recorder.diff = Some(stats);
+        self.apply_recorded(&patches, recorder)
+    }
+
+    fn apply_recorded(&mut self, patches: &[Patch], mut recorder: Recorder) -> Result<(), Error> {
+        self.snapshot();
+        recorder.lap("snapshot");
+        let result = self.apply_patches(patches);
+        recorder.lap("apply");
+        self.reapply_transitions();
+        self.restore();
+        recorder.lap("restore");
+        self.finish(recorder);
+        result
+    }
+
+    fn apply_patches(&mut self, patches: &[Patch]) -> Result<(), Error> {
+        let batch = Batch::new(patches);
+        let mut assembled: HashSet<usize> = HashSet::new();
+
+        for (index, patch) in patches.iter().enumerate() {
+            if assembled.contains(&index) {
+                continue
+            }
+            let before = self.backend.operations();
+            match patch {
+                Patch::Insert { parent, node, item, before } => {
+                    if let Some(created) = self.insert(node, item)? {
+                        // The new subtree is assembled before it is placed, so it reaches the document with a single insertion.
+                        self.assemble(patches, &batch, node, &created, &mut assembled)?;
+                        self.place(parent.as_ref(), node, &created, before.as_ref())?;
+                    }
+                }
+                Patch::Move { parent, node, before } => {
+                    if let Some(existing) = self.nodes.get(node).cloned() {
+                        self.preserve(&existing);
+                        self.place(parent.as_ref(), node, &existing, before.as_ref())?;
+                    }
+                }
+                Patch::Remove { node, descendants } => {
+                    descendants.iter().for_each(|descendant| {
+                        self.release(descendant);
+                        self.forget_transition(descendant);
+                    });
+                    if let Some(existing) = self.release(node) {
+                        if !self.leave(node, &existing) {
+                            self.errors.check(detach(&self.backend, node, &existing))?;
+                        }
+                    }
+                    if self.root == Some(*node) {
+                        self.root = None;
+                    }
+                }
+                Patch::Replace { node, item } => {
+                    let created = match self.errors.check(create(&self.backend, node, item, self.errors))? {
+                        Some(created) => created,
+                        None => continue,
+                    };
+                    if let Some(existing) = self.nodes.get(node).cloned() {
+                        self.preserve(&existing);
+                        let existing = &existing;
+                        if self.backend.tag_name(&created).is_some() {
+                            while let Some(child) = self.backend.first_child(existing) {
+                                let moved = self.backend.insert_before(&created, &child, None)
+                                    .map_err(|cause| Error::dom(node, "move child", cause));
+                                if self.errors.check(moved)?.is_none() {
+                                    break
+                                }
+                            }
+                        }
+                        if let Some(parent) = self.backend.parent(existing) {
+                            let replaced = self.backend.replace_child(&parent, &created, existing)
+                                .map_err(|cause| Error::dom(node, "replace node", cause));
+                            if self.errors.check(replaced)?.is_none() {
+                                continue
+                            }
+                        }
+                    }
+                    self.bind(node, &created, item.listeners())?;
+                    self.track(node, created);
+                }
+                Patch::SetAttribute { node, name, value } => {
+                    if let Some(element) = self.nodes.get(node) {
+                        let set = props::set(&self.backend, element, name, value)
+                            .map_err(|cause| Error::attribute(node, "set attribute", name, cause));
+                        self.errors.check(set)?;
+                    }
+                    self.track_update(node, before);
+                }
+                Patch::RemoveAttribute { node, name } => {
+                    if let Some(element) = self.nodes.get(node) {
+                        let removed = props::remove(&self.backend, element, name)
+                            .map_err(|cause| Error::attribute(node, "remove attribute", name, cause));
+                        self.errors.check(removed)?;
+                    }
+                    self.track_update(node, before);
+                }
+                Patch::SetListener { node, listener } => {
+                    if let Some(existing) = self.nodes.get(node).cloned() {
+                        let mut listeners: Vec<Listener> = self.bound_listeners(node).into_iter()
+                            .filter(|bound| bound.event() != listener.event())
+                            .collect();
+                        listeners.push(listener.clone());
+                        self.bind(node, &existing, &listeners)?;
+                    }
+                }
+                Patch::RemoveListener { node, event } => {
+                    if let Some(existing) = self.nodes.get(node).cloned() {
+                        let listeners: Vec<Listener> = self.bound_listeners(node).into_iter()
+                            .filter(|bound| bound.event() != event)
+                            .collect();
+                        self.bind(node, &existing, &listeners)?;
+                    }
+                }
+                Patch::SetText { node, value } => {
+                    if let Some(text) = self.nodes.get(node) {
+                        self.backend.set_text(text, value.as_str());
+                    }
+                    self.track_update(node, before);
+                }
+            }
+        }
+        // Selects take their value once the options of the whole batch are in place.
+        for patch in patches.iter() {
+            let node = match patch {
+                Patch::Insert { node, item, .. } | Patch::Replace { node, item } if item.attribute("value").is_some() => node,
+                Patch::SetAttribute { node, name, .. } if name == "value" => node,
+                _ => continue,
+            };
+            if let Some(element) = self.nodes.get(node) {
+                let settled = props::settle(&self.backend, element)
+                    .map_err(|cause| Error::attribute(node, "set property", "value", cause));
+                self.errors.check(settled)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, node: &VRef, item: &VItem) -> Result<Option<B::Node>, Error> {
+        self.cancel_leave(node);
+        match self.errors.check(create(&self.backend, node, item, self.errors))? {
+            Some(created) => {
+                self.enter(node, &created);
+                self.bind(node, &created, item.listeners())?;
+                self.track(node, created.clone());
+                Ok(Some(created))
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Inserts the children of a new node which are new as well.
+    fn assemble(&mut self, patches: &[Patch], batch: &Batch, parent: &VRef, parent_node: &B::Node, assembled: &mut HashSet<usize>) -> Result<(), Error> {
+        for index in batch.inserts.get(parent).into_iter().flatten() {
+            if let Patch::Insert { node, item, before, .. } = &patches[*index] {
+                assembled.insert(*index);
+                if let Some(created) = self.insert(node, item)? {
+                    self.assemble(patches, batch, node, &created, assembled)?;
+                    // Siblings which are moved in by a later patch are not there yet, so the node
+                    // goes before the next sibling which is. The move then lands in between.
+                    let mut before = *before;
+                    while let Some(sibling) = before.filter(|sibling| !self.is_child(parent_node, sibling)) {
+                        before = batch.following.get(&sibling).cloned().flatten();
+                    }
+                    let before = before.and_then(|before| self.nodes.get(&before));
+                    let inserted = self.backend.insert_before(parent_node, &created, before)
+                        .map_err(|cause| Error::dom(node, "insert node", cause));
+                    self.errors.check(inserted)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_child(&self, parent: &B::Node, node: &VRef) -> bool {
+        self.nodes.get(node)
+            .and_then(|node| self.backend.parent(node))
+            .is_some_and(|node_parent| self.backend.is_same(&node_parent, parent))
+    }
+
+    fn place(&mut self, parent: Option<&VRef>, node_ref: &VRef, node: &B::Node, before: Option<&VRef>) -> Result<(), Error> {
+        let before = before.and_then(|before| self.nodes.get(before));
+        match parent {
+            None => {
+                let inserted = self.backend.insert_before(&self.container, node, before)
+                    .map_err(|cause| Error::dom(node_ref, "insert root", cause));
+                if self.errors.check(inserted)?.is_some() {
+                    self.root = Some(*node_ref);
+                }
+            }
+            Some(parent) => {
+                if let Some(parent) = self.nodes.get(parent) {
+                    let inserted = self.backend.insert_before(parent, node, before)
+                        .map_err(|cause| Error::dom(node_ref, "insert node", cause));
+                    self.errors.check(inserted)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Batch {
+    // Indices of the insert patches by parent, in patch order.
+    inserts: HashMap<VRef, Vec<usize>>,
+    // The sibling each inserted or moved node is placed before.
+    following: HashMap<VRef, Option<VRef>>,
+}
+
+impl Batch {
+
+    fn new(patches: &[Patch]) -> Batch {
+        let mut inserts: HashMap<VRef, Vec<usize>> = HashMap::new();
+        let mut following: HashMap<VRef, Option<VRef>> = HashMap::new();
+        patches.iter().enumerate().for_each(|(index, patch)| {
+            match patch {
+                Patch::Insert { parent: Some(parent), node, before, .. } => {
+                    inserts.entry(*parent).or_default().push(index);
+                    following.insert(*node, *before);
+                }
+                Patch::Move { parent: Some(_), node, before } => {
+                    following.insert(*node, *before);
+                }
+                _ => {}
+            }
+        });
+        Batch { inserts, following }
+    }
+}
+
+fn create<B: Backend>(backend: &B, node_ref: &VRef, item: &VItem, mode: ErrorMode) -> Result<B::Node, Error> {
+    let mut vnode = VNode::new(*node_ref);
+    vnode.item = Some(item.clone());
+    vnode.upsert(backend, None, mode)
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use vdom::{diff, VItem, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::MountOptions;
+    use crate::testing::{html, memory_mount};
+
+    #[test]
+    fn test_apply() {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let items: Vec<_> = ["a", "b", "c"].iter()
+            .map(|value| {
+                let item = element(&mut tree, Some(&list), "li", &[]);
+                text(&mut tree, &item, value);
+                item
+            })
+            .collect();
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let mut next = tree.clone();
+        next.remove_node(&items[0]);
+        next.append_child(&list, &items[1]);
+        if let Some(item) = next.item_mut(&items[2]) {
+            item.set_attribute("class", "active");
+        }
+        let item = element(&mut next, Some(&list), "li", &[]);
+        text(&mut next, &item, "d");
+        next.update_node(&list, Box::new(|node| {
+            node.item = Some(VItem::Element { name: String::from("ol"), attributes: Vec::new(), listeners: Vec::new() })
+        }));
+        mount.apply(&diff(&tree, &next)).expect("patched");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ol><li class=\"active\">c</li><li>b</li><li>d</li></ol>"));
+        assert_that!(mount.len()).is_equal_to(7);
+    }
+
+    #[test]
+    fn test_apply_batches_new_subtrees() {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let existing = element(&mut tree, Some(&list), "li", &[]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+        mount.reset_operations();
+
+        let mut next = tree.clone();
+        let item = element(&mut next, Some(&list), "li", &[]);
+        let table = element(&mut next, Some(&item), "table", &[]);
+        ["a", "b", "c"].iter().for_each(|value| {
+            let row = element(&mut next, Some(&table), "tr", &[]);
+            text(&mut next, &row, value);
+        });
+        next.append_child(&table, &existing);
+        mount.apply(&diff(&tree, &next)).expect("patched");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li><table><tr>a</tr><tr>b</tr><tr>c</tr><li></li></table></li></ul>"));
+        assert_that!(mount.operations().created).is_equal_to(8);
+        assert_that!(mount.operations().inserted).is_equal_to(7);
+        assert_that!(mount.operations().attached).is_equal_to(2);
+    }
+}
@@ -0,0 +1,197 @@
+use vdom::VItem;
+
+use crate::{Backend, Cause, Property};
+
+// Attributes which only seed the initial state of a control, while its live state is kept in a property.
+fn property(name: &str) -> Option<&'static str> {
+    match name {
+        "value" => Some("value"),
+        "checked" => Some("checked"),
+        "selected" => Some("selected"),
+        "muted" => Some("muted"),
+        "indeterminate" => Some("indeterminate"),
+        _ => None,
+    }
+}
+
+fn is_boolean(name: &str) -> bool {
+    matches!(name,
+        "checked" | "selected" | "disabled" | "readonly" | "required" | "multiple" | "hidden"
+        | "autofocus" | "autoplay" | "controls" | "loop" | "muted" | "open" | "novalidate"
+        | "formnovalidate" | "indeterminate")
+}
+
+// Compares against the attribute, which holds the last rendered value, so whatever the
+// user typed into a control survives a re-render unless the rendered value changed.
+pub(crate) fn changed<B: Backend>(backend: &B, element: &B::Node, name: &str, value: &str) -> bool {
+    let current = backend.get_attribute(element, name);
+    if is_boolean(name) {
+        current.is_none()
+    } else {
+        current.as_deref() != Some(value)
+    }
+}
+
+// A select only takes a value which one of its options has, so its value is left to settle,
+// which runs once the options are rendered.
+fn is_select<B: Backend>(backend: &B, element: &B::Node) -> bool {
+    backend.tag_name(element).is_some_and(|tag_name| tag_name.eq_ignore_ascii_case("select"))
+}
+
+// Whether rendering the item into the existing node sets a select's value, before its options are rendered.
+pub(crate) fn unsettled<B: Backend>(backend: &B, existing: Option<&B::Node>, item: &VItem) -> bool {
+    match (item, item.attribute("value")) {
+        (VItem::Element { name, .. }, Some(value)) if name.eq_ignore_ascii_case("select") => {
+            !existing.is_some_and(|existing| is_select(backend, existing) && !changed(backend, existing, "value", value))
+        }
+        _ => false,
+    }
+}
+
+pub(crate) fn settle<B: Backend>(backend: &B, element: &B::Node) -> Result<(), Cause> {
+    match backend.get_attribute(element, "value") {
+        Some(value) if is_select(backend, element) => backend.set_property(element, "value", Property::Text(value)),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn set<B: Backend>(backend: &B, element: &B::Node, name: &str, value: &str) -> Result<(), Cause> {
+    if is_boolean(name) {
+        backend.set_attribute(element, name, "")?;
+    } else {
+        backend.set_attribute(element, name, value)?;
+    }
+    if name == "value" && is_select(backend, element) {
+        return Ok(())
+    }
+    if let Some(property) = property(name) {
+        let value = if is_boolean(name) { Property::Bool(true) } else { Property::Text(String::from(value)) };
+        backend.set_property(element, property, value)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn remove<B: Backend>(backend: &B, element: &B::Node, name: &str) -> Result<(), Cause> {
+    backend.remove_attribute(element, name)?;
+    if let Some(property) = property(name) {
+        let value = if is_boolean(name) { Property::Bool(false) } else { Property::Text(String::new()) };
+        backend.set_property(element, property, value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use vdom::VTree;
+    use vdom::testing::{element, text};
+
+    use crate::{MountOptions, Property};
+    use crate::testing::memory_mount;
+
+    #[test]
+    fn test_properties() {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let input = element(&mut tree, Some(&form), "input", &[("value", "Jane"), ("disabled", "disabled")]);
+        let checkbox = element(&mut tree, Some(&form), "input", &[("type", "checkbox"), ("checked", "")]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let input_node = mount.get(&input).cloned().expect("input mounted");
+        let checkbox_node = mount.get(&checkbox).cloned().expect("checkbox mounted");
+
+        assert_that!(input_node.attribute("disabled")).is_equal_to(Some(String::new()));
+        assert_that!(input_node.property("value")).is_equal_to(Some(Property::Text(String::from("Jane"))));
+
+        input_node.set_property("value", Property::Text(String::from("Jane Doe")));
+        if let Some(item) = tree.item_mut(&form) {
+            item.set_attribute("class", "login");
+        }
+        mount.update(&tree).expect("updated");
+
+        assert_that!(input_node.property("value")).is_equal_to(Some(Property::Text(String::from("Jane Doe"))));
+
+        if let Some(item) = tree.item_mut(&input) {
+            item.set_attribute("value", "John");
+            item.remove_attribute("disabled");
+        }
+        if let Some(item) = tree.item_mut(&checkbox) {
+            item.remove_attribute("checked");
+        }
+        mount.update(&tree).expect("updated");
+
+        assert_that!(input_node.property("value")).is_equal_to(Some(Property::Text(String::from("John"))));
+        assert_that!(input_node.attribute("disabled")).is_none();
+        assert_that!(checkbox_node.attribute("checked")).is_none();
+        assert_that!(checkbox_node.property("checked")).is_equal_to(Some(Property::Bool(false)));
+    }
+
+    #[test]
+    fn test_boolean_attributes() {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let checkbox = element(&mut tree, Some(&form), "input", &[("type", "checkbox"), ("checked", "checked")]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let checkbox_node = mount.get(&checkbox).cloned().expect("checkbox mounted");
+
+        assert_that!(checkbox_node.attribute("checked")).is_equal_to(Some(String::new()));
+        assert_that!(checkbox_node.property("checked")).is_equal_to(Some(Property::Bool(true)));
+
+        // The user unchecks the box, which a render of the unchanged tree leaves alone.
+        checkbox_node.set_property("checked", Property::Bool(false));
+        mount.update(&tree).expect("rendered again");
+
+        assert_that!(checkbox_node.property("checked")).is_equal_to(Some(Property::Bool(false)));
+
+        if let Some(item) = tree.item_mut(&checkbox) {
+            item.set_attribute("disabled", "false");
+        }
+        mount.update(&tree).expect("updated");
+
+        assert_that!(checkbox_node.attribute("disabled")).is_equal_to(Some(String::new()));
+        assert_that!(checkbox_node.property("checked")).is_equal_to(Some(Property::Bool(false)));
+    }
+
+    #[test]
+    fn test_select_value() {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let select = element(&mut tree, Some(&form), "select", &[("value", "b")]);
+        ["a", "b"].iter().for_each(|value| {
+            let option = element(&mut tree, Some(&select), "option", &[("value", value)]);
+            text(&mut tree, &option, value);
+        });
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let select_node = mount.get(&select).cloned().expect("select mounted");
+
+        assert_that!(select_node.property("value")).is_equal_to(Some(Property::Text(String::from("b"))));
+
+        let mut next = tree.clone();
+        let option = element(&mut next, Some(&select), "option", &[]);
+        text(&mut next, &option, "c");
+        if let Some(item) = next.item_mut(&select) {
+            item.set_attribute("value", "c");
+        }
+        mount.patch(&tree, &next).expect("patched");
+
+        assert_that!(select_node.property("value")).is_equal_to(Some(Property::Text(String::from("c"))));
+
+        let mut replaced = next.clone();
+        replaced.remove_node(&select);
+        let select = element(&mut replaced, Some(&form), "select", &[("value", "e")]);
+        ["d", "e"].iter().for_each(|value| {
+            let option = element(&mut replaced, Some(&select), "option", &[]);
+            text(&mut replaced, &option, value);
+        });
+        mount.patch(&next, &replaced).expect("patched");
+
+        assert_that!(mount.get(&select).and_then(|node| node.property("value")))
+            .is_equal_to(Some(Property::Text(String::from("e"))));
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use log::debug;
+
+use vdom::{DiffStats, VRef};
+
+use crate::{Backend, Clock, Mount, Operations};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct RenderReport {
+    // What was rendered: "update", "apply", "patch", "hydrate" or "sliced".
+    pub render: &'static str,
+    // Milliseconds spent in each phase, all zero when the mount has no clock.
+    pub phases: Vec<(&'static str, f64)>,
+    pub operations: Operations,
+    // Nodes which were kept but had their attributes, properties or text changed.
+    pub updated: usize,
+    pub diff: Option<DiffStats>,
+}
+
+impl RenderReport {
+
+    pub fn duration(&self) -> f64 {
+        self.phases.iter().map(|(_, duration)| duration).sum()
+    }
+
+    pub fn phase(&self, name: &str) -> Option<f64> {
+        self.phases.iter().find(|(phase, _)| *phase == name).map(|(_, duration)| *duration)
+    }
+}
+
+impl Display for RenderReport {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let phases: Vec<String> = self.phases.iter()
+            .map(|(phase, duration)| format!("{} {:.2}ms", phase, duration))
+            .collect();
+        write!(f, "{} in {:.2}ms ({}): {} created, {} updated, {} removed, {} attributes, {} listeners",
+               self.render, self.duration(), phases.join(", "), self.operations.created, self.updated,
+               self.operations.removed, self.operations.attributes + self.operations.properties, self.operations.listeners)?;
+        if let Some(diff) = &self.diff {
+            write!(f, ", {} nodes compared", diff.compared)?;
+        }
+        Ok(())
+    }
+}
+
+type ReportCallback = Box<dyn Fn(&RenderReport)>;
+
+#[derive(Default)]
+pub(crate) struct Instrumentation {
+    clock: Option<Rc<dyn Clock>>,
+    callback: Option<ReportCallback>,
+    last: Option<RenderReport>,
+    updated: HashSet<VRef>,
+}
+
+// Collects the phases of a single render.
+pub(crate) struct Recorder {
+    render: &'static str,
+    clock: Option<Rc<dyn Clock>>,
+    last: f64,
+    phases: Vec<(&'static str, f64)>,
+    operations: Operations,
+    pub(crate) diff: Option<DiffStats>,
+}
+
+impl Recorder {
+
+    pub(crate) fn lap(&mut self, phase: &'static str) {
+        let now = self.now();
+        let duration = now - self.last;
+        self.last = now;
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += duration,
+            None => self.phases.push((phase, duration)),
+        }
+    }
+
+    // Leaves the time since the last lap out of the report, e.g. while a sliced render yields.
+    pub(crate) fn skip(&mut self) {
+        self.last = self.now();
+    }
+
+    fn now(&self) -> f64 {
+        self.clock.as_ref().map(|clock| clock.now()).unwrap_or_default()
+    }
+}
+
+impl<B: Backend> Mount<B> {
+
+    pub fn set_clock<C>(&mut self, clock: C) where C: Clock + 'static {
+        self.instrumentation.clock = Some(Rc::new(clock));
+    }
+
+    // Called with the report of every render, after it has been logged at debug level.
+    pub fn on_render<F>(&mut self, callback: F) where F: Fn(&RenderReport) + 'static {
+        self.instrumentation.callback = Some(Box::new(callback));
+    }
+
+    pub fn last_report(&self) -> Option<&RenderReport> {
+        self.instrumentation.last.as_ref()
+    }
+
+    pub(crate) fn record(&mut self, render: &'static str) -> Recorder {
+        self.instrumentation.updated.clear();
+        let mut recorder = Recorder {
+            render,
+            clock: self.instrumentation.clock.clone(),
+            last: 0.0,
+            phases: Vec::new(),
+            operations: self.backend.operations(),
+            diff: None,
+        };
+        recorder.skip();
+        recorder
+    }
+
+    // Counts the node as updated if any of its content changed since the operations were taken.
+    pub(crate) fn track_update(&mut self, node_ref: &VRef, before: Operations) {
+        let after = self.backend.operations().since(before);
+        if after.attributes + after.properties + after.texts > 0 {
+            self.instrumentation.updated.insert(*node_ref);
+        }
+    }
+
+    pub(crate) fn finish(&mut self, recorder: Recorder) {
+        let report = RenderReport {
+            render: recorder.render,
+            phases: recorder.phases,
+            operations: self.backend.operations().since(recorder.operations),
+            updated: self.instrumentation.updated.drain().count(),
+            diff: recorder.diff,
+        };
+        debug!("{}", report);
+        if let Some(callback) = &self.instrumentation.callback {
+            callback(&report);
+        }
+        self.instrumentation.last = Some(report);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{DiffStats, VItem, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::MountOptions;
+    use crate::testing::{memory_mount, ManualClock};
+
+    fn create_list(items: usize) -> VTree {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        for index in 0..items {
+            let item = element(&mut tree, Some(&list), "li", &[("class", "item")]);
+            text(&mut tree, &item, index.to_string().as_str());
+        }
+        tree
+    }
+
+    #[test]
+    fn test_update_report() {
+        let mut mount = memory_mount(MountOptions::default());
+        mount.set_clock(ManualClock::new(1.0));
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        {
+            let reports = reports.clone();
+            mount.on_render(move |report| reports.borrow_mut().push(report.clone()));
+        }
+        let tree = create_list(100);
+        mount.update(&tree).expect("mounted");
+
+        let report = mount.last_report().cloned().expect("reported");
+        assert_that!(report.render).is_equal_to("update");
+        assert_that!(report.phases).is_equal_to(vec![("snapshot", 1.0), ("release", 1.0), ("render", 1.0), ("restore", 1.0)]);
+        assert_that!(report.operations.created).is_less_than_or_equal_to(201);
+        assert_that!(report.operations.attached).is_less_than_or_equal_to(1);
+        assert_that!(report.updated).is_equal_to(0);
+
+        let mut updated = tree.clone();
+        let items: Vec<_> = updated.children(&tree.get_root().unwrap()).iter().map(|item| item.id).collect();
+        updated.item_mut(&items[3]).unwrap().set_attribute("class", "item selected");
+        updated.remove_node(&items[7]);
+        mount.update(&updated).expect("updated");
+
+        let report = mount.last_report().cloned().expect("reported");
+        assert_that!(report.operations.created).is_equal_to(0);
+        assert_that!(report.operations.removed).is_less_than_or_equal_to(1);
+        assert_that!(report.operations.total()).is_less_than_or_equal_to(2);
+        assert_that!(report.updated).is_equal_to(1);
+        assert_that!(report.duration()).is_equal_to(4.0);
+        assert_that!(reports.borrow().len()).is_equal_to(2);
+    }
+
+    #[test]
+    fn test_patch_report() {
+        let mut mount = memory_mount(MountOptions::default());
+        let tree = create_list(3);
+        mount.update(&tree).expect("mounted");
+
+        let mut updated = tree.clone();
+        let items: Vec<_> = updated.children(&tree.get_root().unwrap()).iter().map(|item| item.id).collect();
+        let label = updated.children(&items[0])[0].id;
+        *updated.item_mut(&label).unwrap() = VItem::Text { value: String::from("zero") };
+        mount.patch(&tree, &updated).expect("patched");
+
+        let report = mount.last_report().cloned().expect("reported");
+        assert_that!(report.render).is_equal_to("patch");
+        assert_that!(report.phase("diff")).is_equal_to(Some(0.0));
+        assert_that!(report.diff).is_equal_to(Some(DiffStats { compared: 7, texts: 1, ..DiffStats::default() }));
+        assert_that!(report.operations.total()).is_less_than_or_equal_to(1);
+        assert_that!(report.updated).is_equal_to(1);
+        assert_that!(report.to_string())
+            .is_equal_to(String::from("patch in 0.00ms (diff 0.00ms, snapshot 0.00ms, apply 0.00ms, restore 0.00ms): 0 created, 1 updated, 0 removed, 0 attributes, 0 listeners, 7 nodes compared"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::cell::{Cell, RefCell};
+use std::mem;
+use std::rc::{Rc, Weak};
+
+use log::warn;
+use wasm_bindgen::prelude::*;
+use web_sys::Window;
+
+use crate::Cause;
+
+pub type Task = Box<dyn FnOnce()>;
+
+pub trait FrameSource {
+
+    // Runs the callback once, before the next repaint.
+    fn request_frame(&self, callback: Task) -> Result<(), Cause>;
+}
+
+pub struct AnimationFrames {
+    window: Window,
+}
+
+impl AnimationFrames {
+
+    pub fn new(window: &Window) -> AnimationFrames {
+        AnimationFrames { window: window.clone() }
+    }
+}
+
+impl FrameSource for AnimationFrames {
+
+    fn request_frame(&self, callback: Task) -> Result<(), Cause> {
+        let closure = Closure::once_into_js(callback);
+        self.window.request_animation_frame(closure.unchecked_ref())?;
+        Ok(())
+    }
+}
+
+struct Render {
+    dirty: Cell<bool>,
+    callback: Box<dyn Fn()>,
+}
+
+#[derive(Default)]
+struct Queue {
+    reads: Vec<Task>,
+    renders: Vec<Rc<Render>>,
+    writes: Vec<Task>,
+}
+
+impl Queue {
+
+    fn is_empty(&self) -> bool {
+        self.reads.is_empty() && self.renders.is_empty() && self.writes.is_empty()
+    }
+}
+
+struct State {
+    frames: Box<dyn FrameSource>,
+    requested: Cell<bool>,
+    queue: RefCell<Queue>,
+}
+
+// Collects DOM work and runs it once per frame: all reads first, so measuring does not
+// force a layout in between writes, then the invalidated renders and the other writes.
+#[derive(Clone)]
+pub struct Scheduler {
+    state: Rc<State>,
+}
+
+impl Scheduler {
+
+    pub fn new<F>(frames: F) -> Scheduler where F: FrameSource + 'static {
+        Scheduler {
+            state: Rc::new(State {
+                frames: Box::new(frames),
+                requested: Cell::new(false),
+                queue: RefCell::new(Queue::default()),
+            })
+        }
+    }
+
+    pub fn read<F>(&self, read: F) where F: FnOnce() + 'static {
+        self.state.queue.borrow_mut().reads.push(Box::new(read));
+        self.request();
+    }
+
+    pub fn write<F>(&self, write: F) where F: FnOnce() + 'static {
+        self.state.queue.borrow_mut().writes.push(Box::new(write));
+        self.request();
+    }
+
+    // Registers a render, which runs at most once per frame no matter how often it is invalidated.
+    pub fn render<F>(&self, render: F) -> Invalidator where F: Fn() + 'static {
+        Invalidator {
+            render: Rc::new(Render { dirty: Cell::new(false), callback: Box::new(render) }),
+            scheduler: Rc::downgrade(&self.state),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.state.queue.borrow().is_empty()
+    }
+
+    // Runs the queued work right away instead of waiting for the next frame.
+    pub fn flush_sync(&self) {
+        flush(&self.state)
+    }
+
+    fn request(&self) {
+        request(&self.state)
+    }
+}
+
+#[derive(Clone)]
+pub struct Invalidator {
+    render: Rc<Render>,
+    scheduler: Weak<State>,
+}
+
+impl Invalidator {
+
+    pub fn invalidate(&self) {
+        if let Some(state) = self.scheduler.upgrade() {
+            if !self.render.dirty.replace(true) {
+                state.queue.borrow_mut().renders.push(self.render.clone());
+                request(&state);
+            }
+        }
+    }
+}
+
+fn request(state: &Rc<State>) {
+    if state.requested.replace(true) {
+        return
+    }
+    let weak = Rc::downgrade(state);
+    let requested = state.frames.request_frame(Box::new(move || {
+        if let Some(state) = weak.upgrade() {
+            state.requested.set(false);
+            flush(&state);
+        }
+    }));
+    if let Err(cause) = requested {
+        warn!("Failed to request a frame, flushing right away: {}", cause);
+        state.requested.set(false);
+        flush(state);
+    }
+}
+
+// Work scheduled while flushing is left for the next frame, so a render which keeps
+// invalidating itself cannot lock up the page.
+fn flush(state: &Rc<State>) {
+    let reads = mem::take(&mut state.queue.borrow_mut().reads);
+    reads.into_iter().for_each(|read| read());
+    // Writes scheduled by the reads, like applying what was measured, still belong to this frame.
+    let (renders, writes) = {
+        let mut queue = state.queue.borrow_mut();
+        (mem::take(&mut queue.renders), mem::take(&mut queue.writes))
+    };
+    renders.iter().for_each(|render| {
+        render.dirty.set(false);
+        (render.callback)();
+    });
+    writes.into_iter().for_each(|write| write());
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use crate::{Invalidator, Scheduler};
+    use crate::testing::ManualFrames;
+
+    #[test]
+    fn test_batching() {
+        let frames = ManualFrames::default();
+        let scheduler = Scheduler::new(frames.clone());
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let invalidator = {
+            let calls = calls.clone();
+            scheduler.render(move || calls.borrow_mut().push("render"))
+        };
+        let record = |name: &'static str| {
+            let calls = calls.clone();
+            move || calls.borrow_mut().push(name)
+        };
+
+        invalidator.invalidate();
+        scheduler.write(record("write"));
+        invalidator.invalidate();
+        {
+            let scheduler = scheduler.clone();
+            let record = record("measured");
+            scheduler.clone().read(move || scheduler.write(record));
+        }
+
+        assert_that!(*calls.borrow()).is_empty();
+        assert_that!(frames.requested()).is_equal_to(1);
+
+        frames.tick();
+
+        assert_that!(*calls.borrow()).is_equal_to(vec!["render", "write", "measured"]);
+        assert_that!(scheduler.is_pending()).is_false();
+
+        invalidator.invalidate();
+        scheduler.flush_sync();
+        invalidator.invalidate();
+        frames.tick();
+
+        assert_that!(*calls.borrow()).is_equal_to(vec!["render", "write", "measured", "render", "render"]);
+        assert_that!(frames.requested()).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_invalidate_while_rendering() {
+        let frames = ManualFrames::default();
+        let scheduler = Scheduler::new(frames.clone());
+        let renders = Rc::new(RefCell::new(0));
+        let invalidator: Rc<RefCell<Option<Invalidator>>> = Rc::new(RefCell::new(None));
+        let render = {
+            let renders = renders.clone();
+            let invalidator = invalidator.clone();
+            scheduler.render(move || {
+                *renders.borrow_mut() += 1;
+                if let Some(invalidator) = invalidator.borrow().as_ref() {
+                    invalidator.invalidate();
+                }
+            })
+        };
+        invalidator.replace(Some(render.clone()));
+
+        render.invalidate();
+        frames.tick();
+        frames.tick();
+
+        assert_that!(*renders.borrow()).is_equal_to(2);
+        assert_that!(frames.requested()).is_equal_to(1);
+    }
+}
@@ -0,0 +1,147 @@
+use js_sys::Array;
+use web_sys::{CssStyleSheet, Element, ShadowRoot, ShadowRootInit, ShadowRootMode};
+
+use vdom::VTree;
+
+use crate::{Backend, Cause, Error, Mount, MountOptions, WebBackend};
+
+impl Mount<WebBackend> {
+
+    // Attaches a shadow root to the host and mounts into it, so the styles of the page stay out.
+    // Keep the mount around for closed roots, its container is the only way to reach them.
+    pub fn in_shadow_root(host: &Element, mode: ShadowRootMode, stylesheet: &str, options: MountOptions) -> Result<Mount, Error> {
+        let shadow_root = host.attach_shadow(&ShadowRootInit::new(mode))
+            .map_err(|cause| Error::container("attach shadow root", cause.into()))?;
+        let backend = WebBackend::for_shadow_root(&shadow_root)?;
+        add_stylesheet(&backend, &shadow_root, stylesheet)
+            .map_err(|cause| Error::container("add stylesheet", cause))?;
+        Ok(Mount::with_web_backend(backend, &shadow_root.clone().into(), options))
+    }
+
+    pub fn with_shadow_root(shadow_root: &ShadowRoot, options: MountOptions) -> Result<Mount, Error> {
+        let backend = WebBackend::for_shadow_root(shadow_root)?;
+        Ok(Mount::with_web_backend(backend, &shadow_root.clone().into(), options))
+    }
+}
+
+pub fn mount_in_shadow_root(tree: &VTree, host: &Element, mode: ShadowRootMode, stylesheet: &str, options: MountOptions) -> Result<Mount, Error> {
+    let mut mount = Mount::in_shadow_root(host, mode, stylesheet, options)?;
+    mount.update(tree)?;
+    Ok(mount)
+}
+
+// An adopted stylesheet is not part of the tree, browsers without them get a style element
+// in front of the mounted root instead.
+fn add_stylesheet(backend: &WebBackend, shadow_root: &ShadowRoot, stylesheet: &str) -> Result<(), Cause> {
+    match CssStyleSheet::new() {
+        Ok(sheet) => {
+            sheet.replace_sync(stylesheet)?;
+            shadow_root.set_adopted_style_sheets(&Array::of1(&sheet));
+            Ok(())
+        }
+        Err(_) => insert_style(backend, &shadow_root.clone().into(), stylesheet),
+    }
+}
+
+fn insert_style<B: Backend>(backend: &B, container: &B::Node, stylesheet: &str) -> Result<(), Cause> {
+    let style = backend.create_element("style")?;
+    let text = backend.create_text(stylesheet)?;
+    backend.insert_before(&style, &text, None)?;
+    backend.insert_before(container, &style, backend.first_child(container).as_ref())
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{Listener, VRef, VTree};
+    use vdom::testing::{element, text};
+
+    use crate::{Backend, EventMode, MemoryBackend, MemoryNode, Mount, MountOptions};
+    use crate::testing::html;
+
+    use super::insert_style;
+
+    fn create_form(clicks: &Rc<RefCell<usize>>) -> (VTree, VRef, VRef, VRef) {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let input = element(&mut tree, Some(&form), "input", &[]);
+        let button = element(&mut tree, Some(&form), "button", &[]);
+        text(&mut tree, &button, "Send");
+        let clicks = clicks.clone();
+        if let Some(item) = tree.item_mut(&button) {
+            item.add_listener(Listener::new("click", move |_| *clicks.borrow_mut() += 1));
+        }
+        (tree, form, input, button)
+    }
+
+    // Mounts into a shadow root of a host inside a page, with the style element fallback.
+    fn shadow_mount(open: bool, options: MountOptions) -> (MemoryBackend, MemoryNode, Mount<MemoryBackend>) {
+        let document = MemoryBackend::new();
+        let page = MemoryNode::element("body");
+        let host = MemoryNode::element("app-widget");
+        document.insert_before(&page, &host, None).expect("host inserted");
+        let shadow_root = host.attach_shadow(open).expect("shadow root attached");
+        let backend = document.for_shadow_root(&shadow_root);
+        insert_style(&backend, &shadow_root, "p { color: red }").expect("style inserted");
+        (document, host, Mount::with_backend(backend, &shadow_root, options))
+    }
+
+    #[test]
+    fn test_open_shadow_root() {
+        let clicks = Rc::new(RefCell::new(0));
+        let (tree, form, input, button) = create_form(&clicks);
+        let (document, host, mut mount) = shadow_mount(true, MountOptions { events: EventMode::Delegated, ..MountOptions::default() });
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(html(&mount))
+            .is_equal_to(String::from("<style>p { color: red }</style><form><input></input><button>Send</button></form>"));
+        assert_that!(host.to_html()).is_equal_to(String::from("<app-widget></app-widget>"));
+        assert_that!(host.shadow_root().is_some_and(|shadow_root| shadow_root.is_same(mount.container()))).is_true();
+
+        mount.get(&button).expect("button mounted").dispatch("click");
+
+        assert_that!(*clicks.borrow()).is_equal_to(1);
+
+        let input_node = mount.get(&input).cloned().expect("input mounted");
+        mount.backend().focus(&input_node);
+
+        assert_that!(document.active_element().is_some_and(|active| active.is_same(&host))).is_true();
+        assert_that!(mount.backend().active_element().is_some_and(|active| active.is_same(&input_node))).is_true();
+
+        let mut moved = tree.clone();
+        moved.insert_before(&form, &button, &input);
+        mount.update(&moved).expect("updated");
+
+        assert_that!(html(&mount))
+            .is_equal_to(String::from("<style>p { color: red }</style><form><button>Send</button><input></input></form>"));
+        assert_that!(mount.backend().active_element().is_some_and(|active| active.is_same(&input_node))).is_true();
+    }
+
+    #[test]
+    fn test_closed_shadow_root() {
+        let clicks = Rc::new(RefCell::new(0));
+        let (tree, _, _, button) = create_form(&clicks);
+        let (document, host, mut mount) = shadow_mount(false, MountOptions::default());
+        let host_clicks = Rc::new(RefCell::new(0));
+        let _subscription = {
+            let host_clicks = host_clicks.clone();
+            document.listen(&host, "click", false, Rc::new(move |_| *host_clicks.borrow_mut() += 1)).expect("listening")
+        };
+        mount.update(&tree).expect("mounted");
+
+        assert_that!(host.shadow_root()).is_none();
+
+        mount.get(&button).expect("button mounted").dispatch("click");
+
+        assert_that!(*clicks.borrow()).is_equal_to(1);
+        assert_that!(*host_clicks.borrow()).is_equal_to(1);
+
+        mount.unmount().expect("unmounted");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<style>p { color: red }</style>"));
+    }
+}
@@ -0,0 +1,383 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::mem;
+use std::rc::Rc;
+
+use log::error;
+use web_sys::{Performance, Window};
+
+use vdom::{VRef, VTree};
+
+use crate::{Backend, Error, FrameSource, Mount, VNodeLink};
+use crate::mount::detach;
+use crate::report::Recorder;
+
+pub trait Clock {
+
+    // Milliseconds since an arbitrary but fixed point in time.
+    fn now(&self) -> f64;
+}
+
+pub struct PerformanceClock {
+    performance: Performance,
+}
+
+impl PerformanceClock {
+
+    pub fn new(window: &Window) -> Option<PerformanceClock> {
+        window.performance().map(|performance| PerformanceClock { performance })
+    }
+}
+
+impl Clock for PerformanceClock {
+
+    fn now(&self) -> f64 {
+        self.performance.now()
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Progress {
+    Pending,
+    Done,
+}
+
+// A tree being rendered off-document. Until it is done the DOM is left untouched,
+// so dropping it, e.g. because a newer tree came in, is all it takes to interrupt it.
+// The commit replaces every node, unchanged ones as well. Focus, selection and scroll offsets
+// are carried over to the nodes with the same VRef, any other live state is lost, e.g. what
+// the user typed into an input. Trees which mostly stay the same are better rendered with update.
+pub struct SlicedRender<B: Backend> {
+    tree: VTree,
+    pending: Vec<(VRef, Option<B::Node>)>,
+    created: HashMap<VRef, B::Node>,
+    recorder: Option<Recorder>,
+}
+
+impl<B: Backend> SlicedRender<B> {
+
+    pub fn new(tree: VTree) -> SlicedRender<B> {
+        let pending = tree.get_root().map(|root| (root, None)).into_iter().collect();
+        SlicedRender {
+            tree,
+            pending,
+            created: HashMap::new(),
+            recorder: None,
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<B: Backend> Mount<B> {
+
+    // Creates nodes until the budget is used up, at least one per call so the render
+    // always makes progress. Once all are created the new tree replaces the current one.
+    pub fn resume(&mut self, render: &mut SlicedRender<B>, clock: &dyn Clock, budget: f64) -> Result<Progress, Error> {
+        let mut recorder = render.recorder.take().unwrap_or_else(|| self.record("sliced"));
+        recorder.skip();
+        let deadline = clock.now() + budget;
+        while let Some((node_ref, parent)) = render.pending.pop() {
+            self.build(render, &node_ref, parent.as_ref())?;
+            if !render.pending.is_empty() && clock.now() >= deadline {
+                recorder.lap("build");
+                render.recorder = Some(recorder);
+                return Ok(Progress::Pending)
+            }
+        }
+        recorder.lap("build");
+        self.snapshot();
+        recorder.lap("snapshot");
+        let result = self.commit(render);
+        recorder.lap("commit");
+        self.restore();
+        recorder.lap("restore");
+        self.finish(recorder);
+        result.map(|_| Progress::Done)
+    }
+
+    fn build(&mut self, render: &mut SlicedRender<B>, node_ref: &VRef, parent: Option<&B::Node>) -> Result<(), Error> {
+        let vnode = match render.tree.get_node(node_ref) {
+            Some(vnode) => vnode,
+            None => return Ok(()),
+        };
+        let node = match self.errors.check(vnode.upsert(&self.backend, None, self.errors))? {
+            Some(node) => node,
+            None => return Ok(()),
+        };
+        if let Some(parent) = parent {
+            let appended = self.backend.insert_before(parent, &node, None)
+                .map_err(|cause| Error::dom(node_ref, "insert child", cause));
+            if self.errors.check(appended)?.is_none() {
+                return Ok(())
+            }
+        }
+        render.tree.children(node_ref).iter().rev()
+            .for_each(|child| render.pending.push((child.id, Some(node.clone()))));
+        render.created.insert(*node_ref, node);
+        Ok(())
+    }
+
+    fn commit(&mut self, render: &mut SlicedRender<B>) -> Result<(), Error> {
+        let previous = self.root.and_then(|root| self.nodes.get(&root).cloned().map(|node| (root, node)));
+        // The new tree replaces all nodes, which take over the state of the nodes they replace.
+        if let Some((_, previous)) = &previous {
+            self.preserve(previous);
+        }
+        // Running transitions would hold on to nodes which are about to be replaced.
+        self.cancel_transitions();
+        let node_refs: Vec<VRef> = self.nodes.keys().cloned().collect();
+        node_refs.iter().for_each(|node_ref| {
+            self.release(node_ref);
+            if !render.created.contains_key(node_ref) {
+                self.forget_transition(node_ref);
+            }
+        });
+
+        let created = mem::take(&mut render.created);
+        self.root = render.tree.get_root();
+        let root = self.root.and_then(|root| created.get(&root).cloned());
+        match (previous, root) {
+            (Some((previous_ref, previous)), Some(root)) if self.backend.parent(&previous).is_some_and(|parent| self.backend.is_same(&parent, &self.container)) => {
+                let replaced = self.backend.replace_child(&self.container, &root, &previous)
+                    .map_err(|cause| Error::dom(&previous_ref, "replace root", cause));
+                self.errors.check(replaced)?;
+            }
+            (previous, root) => {
+                if let Some((previous_ref, previous)) = previous {
+                    self.errors.check(detach(&self.backend, &previous_ref, &previous))?;
+                }
+                if let (Some(root_ref), Some(root)) = (self.root, root) {
+                    let appended = self.backend.insert_before(&self.container, &root, None)
+                        .map_err(|cause| Error::dom(&root_ref, "append root", cause));
+                    self.errors.check(appended)?;
+                }
+            }
+        }
+
+        // Listeners are bound last, the bindings of the previous tree were in use until now.
+        // Only nodes new to the mount enter, the others merely replace what was there.
+        for (node_ref, node) in created {
+            let listeners = render.tree.item(&node_ref).map(|item| item.listeners().to_vec()).unwrap_or_default();
+            self.bind(&node_ref, &node, &listeners)?;
+            if !node_refs.contains(&node_ref) {
+                self.enter(&node_ref, &node);
+            }
+            self.track(&node_ref, node);
+        }
+        Ok(())
+    }
+}
+
+// Drives sliced renders, yielding to the frame source between slices.
+#[derive(Clone)]
+pub struct TimeSlicer {
+    clock: Rc<dyn Clock>,
+    frames: Rc<dyn FrameSource>,
+    budget: f64,
+}
+
+impl TimeSlicer {
+
+    pub fn new<C, F>(clock: C, frames: F, budget: f64) -> TimeSlicer where C: Clock + 'static, F: FrameSource + 'static {
+        TimeSlicer {
+            clock: Rc::new(clock),
+            frames: Rc::new(frames),
+            budget,
+        }
+    }
+
+    pub fn render<B, F>(&self, mount: &Rc<RefCell<Mount<B>>>, tree: VTree, done: F) -> RenderHandle where B: Backend, F: FnOnce(Result<(), Error>) + 'static {
+        let handle = RenderHandle { state: Rc::new(Cell::new(RenderState::Running)) };
+        let job = Job {
+            slicer: self.clone(),
+            mount: mount.clone(),
+            render: SlicedRender::new(tree),
+            handle: handle.clone(),
+            done: Box::new(done),
+        };
+        job.step();
+        handle
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum RenderState {
+    Running,
+    Cancelled,
+    Finished,
+}
+
+#[derive(Clone)]
+pub struct RenderHandle {
+    state: Rc<Cell<RenderState>>,
+}
+
+impl RenderHandle {
+
+    // Stops a render which has not been committed yet, leaving the DOM as it was.
+    pub fn cancel(&self) {
+        if self.state.get() == RenderState::Running {
+            self.state.set(RenderState::Cancelled);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.get() == RenderState::Finished
+    }
+}
+
+struct Job<B: Backend> {
+    slicer: TimeSlicer,
+    mount: Rc<RefCell<Mount<B>>>,
+    render: SlicedRender<B>,
+    handle: RenderHandle,
+    done: Box<dyn FnOnce(Result<(), Error>)>,
+}
+
+impl<B: Backend> Job<B> {
+
+    fn step(mut self) {
+        if self.handle.state.get() == RenderState::Cancelled {
+            return
+        }
+        // The mount may be borrowed by whoever yielded to us, in which case we try again next time.
+        let progress = match self.mount.try_borrow_mut() {
+            Ok(mut mount) => Some(mount.resume(&mut self.render, self.slicer.clock.as_ref(), self.slicer.budget)),
+            Err(_) => None,
+        };
+        match progress {
+            Some(Ok(Progress::Done)) => {
+                self.handle.state.set(RenderState::Finished);
+                (self.done)(Ok(()))
+            }
+            Some(Err(error)) => {
+                self.handle.state.set(RenderState::Finished);
+                (self.done)(Err(error))
+            }
+            Some(Ok(Progress::Pending)) | None => {
+                let frames = self.slicer.frames.clone();
+                if let Err(cause) = frames.request_frame(Box::new(move || self.step())) {
+                    error!("Failed to yield the sliced render: {}", cause);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::VTree;
+    use vdom::testing::{element, text};
+
+    use crate::{Backend, MountOptions, Progress, SlicedRender, TimeSlicer, Timing, Transition};
+    use crate::testing::{html, memory_mount, ManualClock, ManualFrames, ManualTimer};
+
+    fn create_list(items: usize) -> VTree {
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        for index in 0..items {
+            let item = element(&mut tree, Some(&list), "li", &[]);
+            text(&mut tree, &item, index.to_string().as_str());
+        }
+        tree
+    }
+
+    #[test]
+    fn test_resume() {
+        let clock = ManualClock::new(1.0);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&create_list(1)).expect("mounted");
+        let mut render = SlicedRender::new(create_list(3));
+
+        assert_that!(mount.resume(&mut render, &clock, 3.0)).is_equal_to(Ok(Progress::Pending));
+        assert_that!(render.remaining()).is_equal_to(2);
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li>0</li></ul>"));
+
+        mount.reset_operations();
+        while mount.resume(&mut render, &clock, 3.0).expect("rendered") == Progress::Pending {}
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li>0</li><li>1</li><li>2</li></ul>"));
+        assert_that!(mount.operations().attached).is_equal_to(1);
+        assert_that!(mount.len()).is_equal_to(7);
+    }
+
+    #[test]
+    fn test_time_slicer() {
+        let frames = ManualFrames::default();
+        let slicer = TimeSlicer::new(ManualClock::new(1.0), frames.clone(), 10.0);
+        let mount = Rc::new(RefCell::new(memory_mount(MountOptions::default())));
+        let result = Rc::new(RefCell::new(None));
+
+        let cancelled = slicer.render(&mount, create_list(20), |_| {});
+        cancelled.cancel();
+        let handle = {
+            let result = result.clone();
+            slicer.render(&mount, create_list(20), move |done| {
+                result.replace(Some(done));
+            })
+        };
+        let mut frames_taken = 0;
+        while !handle.is_finished() {
+            assert_that!(html(&mount.borrow())).is_empty();
+            frames.tick();
+            frames_taken += 1;
+        }
+
+        assert_that!(frames_taken).is_equal_to(4);
+        assert_that!(*result.borrow()).is_equal_to(Some(Ok(())));
+        assert_that!(mount.borrow().len()).is_equal_to(41);
+        assert_that!(frames.requested()).is_equal_to(0);
+    }
+
+    #[test]
+    fn test_commit_transitions_and_state() {
+        let clock = ManualClock::new(1.0);
+        let frames = ManualFrames::default();
+        let timer = ManualTimer::default();
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let input = element(&mut tree, Some(&form), "input", &[]);
+        let removed = element(&mut tree, Some(&form), "p", &[]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.set_timing(Timing::new(frames.clone(), timer.clone()));
+        mount.update(&tree).expect("mounted");
+
+        let backend = mount.backend().clone();
+        backend.focus(mount.get(&input).expect("input mounted"));
+        backend.set_scroll(mount.get(&form).expect("form mounted"), (0, 30));
+        mount.add_transition(&form, Transition::new("fade", 300.0));
+        mount.add_transition(&removed, Transition::new("fade", 300.0));
+
+        let mut next = tree.clone();
+        next.remove_node(&removed);
+        let added = element(&mut next, Some(&form), "span", &[]);
+        mount.add_transition(&added, Transition::new("fade", 300.0));
+        let mut render = SlicedRender::new(next);
+        while mount.resume(&mut render, &clock, 100.0).expect("rendered") == Progress::Pending {}
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<form><input></input><span class=\"fade-enter-from fade-enter-active\"></span></form>"));
+        assert_that!(mount.has_transition(&form)).is_true();
+        assert_that!(mount.has_transition(&added)).is_true();
+        assert_that!(mount.has_transition(&removed)).is_false();
+        assert_that!(backend.active_element().is_some_and(|node| mount.get(&input).is_some_and(|input| input.is_same(&node)))).is_true();
+        assert_that!(backend.scroll(mount.get(&form).expect("form mounted"))).is_equal_to((0, 30));
+
+        let entering = mount.get(&added).cloned().expect("span mounted");
+        let mut render = SlicedRender::new(VTree::new());
+        while mount.resume(&mut render, &clock, 100.0).expect("rendered") == Progress::Pending {}
+        timer.run();
+
+        assert_that!(html(&mount)).is_empty();
+        assert_that!(entering.attribute("class")).is_none();
+        assert_that!(mount.has_transition(&form)).is_false();
+        assert_that!(mount.has_transition(&added)).is_false();
+    }
+}
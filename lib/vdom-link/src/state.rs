@@ -0,0 +1,154 @@
+use std::mem;
+
+use vdom::VRef;
+
+use crate::{Backend, Mount, Selection};
+
+// The parts of the DOM state which a browser drops when a node is detached, even if it is
+// reinserted right away, and which the user would notice losing.
+#[derive(Default)]
+pub(crate) struct Snapshot {
+    active: Option<VRef>,
+    selection: Option<Selection>,
+    scroll: Vec<(VRef, (i32, i32))>,
+}
+
+impl<B: Backend> Mount<B> {
+
+    // Takes the focus and selection. Scroll offsets are only taken from nodes about to be moved or
+    // replaced, reading them from the whole tree would force a layout on every render.
+    pub(crate) fn snapshot(&mut self) {
+        let active = self.backend.active_element().and_then(|active| {
+            self.backend.get_ref(&active)
+                .filter(|node_ref| self.nodes.get(node_ref).is_some_and(|node| self.backend.is_same(node, &active)))
+        });
+        let selection = active.and_then(|active| self.nodes.get(&active)).and_then(|node| self.backend.selection(node));
+        self.preserved = Snapshot { active, selection, scroll: Vec::new() };
+    }
+
+    // Called before the node is moved or replaced, for the offsets in its subtree.
+    pub(crate) fn preserve(&mut self, node: &B::Node) {
+        let mut pending = vec![node.clone()];
+        while let Some(node) = pending.pop() {
+            if self.backend.tag_name(&node).is_none() {
+                continue
+            }
+            if let Some(node_ref) = self.backend.get_ref(&node) {
+                let offsets = self.backend.scroll(&node);
+                if offsets != (0, 0) && !self.preserved.scroll.iter().any(|(preserved, _)| *preserved == node_ref) {
+                    self.preserved.scroll.push((node_ref, offsets));
+                }
+            }
+            let mut index = 0;
+            while let Some(child) = self.backend.child_at(&node, index) {
+                pending.push(child);
+                index += 1;
+            }
+        }
+    }
+
+    // Tags the node with its VRef, through which the preserved state finds its way back.
+    pub(crate) fn track(&mut self, node_ref: &VRef, node: B::Node) {
+        // Best effort, an untagged node only loses its state when it is moved.
+        let _ = self.backend.set_ref(&node, node_ref);
+        self.nodes.insert(*node_ref, node);
+    }
+
+    // Applied to whichever node represents a VRef now, so replaced nodes take over the state as well.
+    pub(crate) fn restore(&mut self) {
+        let snapshot = mem::take(&mut self.preserved);
+        if let Some(node) = snapshot.active.and_then(|active| self.nodes.get(&active)) {
+            if !self.backend.active_element().is_some_and(|active| self.backend.is_same(&active, node)) {
+                self.backend.focus(node);
+            }
+            if let Some(selection) = snapshot.selection {
+                if self.backend.selection(node).as_ref() != Some(&selection) {
+                    self.backend.set_selection(node, &selection);
+                }
+            }
+        }
+        for (node_ref, offsets) in snapshot.scroll {
+            if let Some(node) = self.nodes.get(&node_ref) {
+                if self.backend.scroll(node) != offsets {
+                    self.backend.set_scroll(node, offsets);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use vdom::{Patch, VTree};
+    use vdom::testing::element;
+
+    use crate::{Backend, MountOptions, Property, Selection};
+    use crate::testing::memory_mount;
+
+    #[test]
+    fn test_preserve_state() {
+        let mut tree = VTree::new();
+        let form = element(&mut tree, None, "form", &[]);
+        let list = element(&mut tree, Some(&form), "ul", &[]);
+        let input = element(&mut tree, Some(&form), "input", &[("value", "secret")]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let backend = mount.backend().clone();
+        let input_node = mount.get(&input).cloned().expect("input mounted");
+        let list_node = mount.get(&list).cloned().expect("list mounted");
+        backend.focus(&input_node);
+        backend.set_selection(&input_node, &Selection { start: 2, end: 4, direction: Some(String::from("forward")) });
+        backend.set_scroll(&list_node, (0, 120));
+
+        let mut moved = tree.clone();
+        moved.insert_before(&form, &input, &list);
+        mount.update(&moved).expect("updated");
+
+        assert_that!(mount.get(&input).is_some_and(|node| node.is_same(&input_node))).is_true();
+        assert_that!(backend.active_element().is_some_and(|node| node.is_same(&input_node))).is_true();
+        assert_that!(backend.selection(&input_node)).is_equal_to(Some(Selection { start: 2, end: 4, direction: Some(String::from("forward")) }));
+        assert_that!(backend.scroll(&list_node)).is_equal_to((0, 120));
+
+        mount.apply(&[
+            Patch::Move { parent: Some(form), node: input, before: None },
+            Patch::Move { parent: Some(form), node: list, before: None },
+            Patch::SetAttribute { node: input, name: String::from("value"), value: String::from("secret") },
+        ]).expect("applied");
+
+        assert_that!(backend.active_element().is_some_and(|node| node.is_same(&input_node))).is_true();
+        assert_that!(backend.selection(&input_node).map(|selection| selection.start)).is_equal_to(Some(2));
+        assert_that!(backend.scroll(&list_node)).is_equal_to((0, 120));
+        assert_that!(input_node.property("value")).is_equal_to(Some(Property::Text(String::from("secret"))));
+    }
+
+    #[test]
+    fn test_preserve_moved_subtree() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        let section = element(&mut tree, Some(&div), "section", &[]);
+        let list = element(&mut tree, Some(&section), "ol", &[]);
+        let aside = element(&mut tree, Some(&div), "aside", &[]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.update(&tree).expect("mounted");
+
+        let backend = mount.backend().clone();
+        let list_node = mount.get(&list).cloned().expect("list mounted");
+        let aside_node = mount.get(&aside).cloned().expect("aside mounted");
+        backend.set_scroll(&list_node, (0, 80));
+        backend.set_scroll(&aside_node, (0, 40));
+
+        let mut moved = tree.clone();
+        moved.insert_before(&div, &aside, &section);
+        mount.update(&moved).expect("updated");
+
+        assert_that!(backend.scroll(&list_node)).is_equal_to((0, 80));
+        assert_that!(backend.scroll(&aside_node)).is_equal_to((0, 40));
+
+        mount.apply(&[Patch::Move { parent: Some(div), node: section, before: Some(aside) }]).expect("applied");
+
+        assert_that!(backend.scroll(&list_node)).is_equal_to((0, 80));
+    }
+}
@@ -0,0 +1,86 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::{Backend, Cause, Clock, FrameSource, Timer, MemoryBackend, MemoryNode, Mount, MountOptions, Task};
+
+pub(crate) fn memory_mount(options: MountOptions) -> Mount<MemoryBackend> {
+    let backend = MemoryBackend::new();
+    let container = backend.create_element("app").expect("container created");
+    Mount::with_backend(backend, &container, options)
+}
+
+pub(crate) fn html(mount: &Mount<MemoryBackend>) -> String {
+    mount.container().children().iter().map(MemoryNode::to_html).collect()
+}
+
+// Frames which only run when the test ticks them.
+#[derive(Clone, Default)]
+pub(crate) struct ManualFrames {
+    callbacks: Rc<RefCell<Vec<Task>>>,
+}
+
+impl ManualFrames {
+
+    pub(crate) fn requested(&self) -> usize {
+        self.callbacks.borrow().len()
+    }
+
+    pub(crate) fn tick(&self) {
+        let callbacks = self.callbacks.take();
+        callbacks.into_iter().for_each(|callback| callback());
+    }
+}
+
+impl FrameSource for ManualFrames {
+
+    fn request_frame(&self, callback: Task) -> Result<(), Cause> {
+        self.callbacks.borrow_mut().push(callback);
+        Ok(())
+    }
+}
+
+// A clock which advances by a fixed step whenever it is read.
+pub(crate) struct ManualClock {
+    now: Cell<f64>,
+    step: f64,
+}
+
+impl ManualClock {
+
+    pub(crate) fn new(step: f64) -> ManualClock {
+        ManualClock { now: Cell::new(0.0), step }
+    }
+}
+
+impl Clock for ManualClock {
+
+    fn now(&self) -> f64 {
+        let now = self.now.get();
+        self.now.set(now + self.step);
+        now
+    }
+}
+
+// Timers which only fire when the test runs them, regardless of their delay.
+#[derive(Clone, Default)]
+pub(crate) struct ManualTimer {
+    callbacks: Rc<RefCell<Vec<Task>>>,
+}
+
+impl ManualTimer {
+
+    pub(crate) fn run(&self) {
+        while !self.callbacks.borrow().is_empty() {
+            let callbacks = self.callbacks.take();
+            callbacks.into_iter().for_each(|callback| callback());
+        }
+    }
+}
+
+impl Timer for ManualTimer {
+
+    fn set_timeout(&self, _delay: f64, callback: Task) -> Result<(), Cause> {
+        self.callbacks.borrow_mut().push(callback);
+        Ok(())
+    }
+}
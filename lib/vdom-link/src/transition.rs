@@ -0,0 +1,467 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use log::warn;
+use wasm_bindgen::prelude::*;
+use web_sys::Window;
+
+use vdom::VRef;
+
+use crate::{AnimationFrames, Backend, Callback, Cause, FrameSource, Mount, Task};
+use crate::mount::detach;
+use crate::operations::Counted;
+
+// Classes are named after the transition, e.g. "fade-enter-from", "fade-enter-active" and
+// "fade-enter-to", like in Vue. A transition which does not end in time is cut short.
+#[derive(PartialEq, Debug, Clone)]
+pub struct Transition {
+    name: String,
+    timeout: f64,
+}
+
+impl Transition {
+
+    pub fn new(name: &str, timeout: f64) -> Transition {
+        Transition { name: String::from(name), timeout }
+    }
+
+    fn class(&self, phase: Phase, stage: &str) -> String {
+        match phase {
+            Phase::Enter => format!("{}-enter-{}", self.name, stage),
+            Phase::Leave => format!("{}-leave-{}", self.name, stage),
+        }
+    }
+}
+
+pub trait Timer {
+
+    // Runs the callback once, after the delay in milliseconds.
+    fn set_timeout(&self, delay: f64, callback: Task) -> Result<(), Cause>;
+}
+
+pub struct WindowTimer {
+    window: Window,
+}
+
+impl WindowTimer {
+
+    pub fn new(window: &Window) -> WindowTimer {
+        WindowTimer { window: window.clone() }
+    }
+}
+
+impl Timer for WindowTimer {
+
+    fn set_timeout(&self, delay: f64, callback: Task) -> Result<(), Cause> {
+        let closure = Closure::once_into_js(callback);
+        self.window.set_timeout_with_callback_and_timeout_and_arguments_0(closure.unchecked_ref(), delay as i32)?;
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Timing {
+    frames: Rc<dyn FrameSource>,
+    timer: Rc<dyn Timer>,
+}
+
+impl Timing {
+
+    pub fn new<F, T>(frames: F, timer: T) -> Timing where F: FrameSource + 'static, T: Timer + 'static {
+        Timing {
+            frames: Rc::new(frames),
+            timer: Rc::new(timer),
+        }
+    }
+
+    pub fn web(window: &Window) -> Timing {
+        Timing::new(AnimationFrames::new(window), WindowTimer::new(window))
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Phase {
+    Enter,
+    Leave,
+}
+
+struct Running<B: Backend> {
+    id: usize,
+    phase: Phase,
+    node: B::Node,
+    transition: Transition,
+    classes: Vec<String>,
+    subscriptions: Vec<B::Subscription>,
+}
+
+type Registry<B> = RefCell<HashMap<VRef, Running<B>>>;
+
+type Active<B> = Rc<Registry<B>>;
+
+pub(crate) struct Transitions<B: Backend> {
+    registered: HashMap<VRef, Transition>,
+    timing: Option<Timing>,
+    active: Active<B>,
+    next_id: usize,
+}
+
+impl<B: Backend> Default for Transitions<B> {
+
+    fn default() -> Self {
+        Transitions {
+            registered: HashMap::new(),
+            timing: None,
+            active: Rc::new(RefCell::new(HashMap::new())),
+            next_id: 0,
+        }
+    }
+}
+
+impl<B: Backend> Mount<B> {
+
+    // Inserted nodes run the enter transition, removed ones the leave transition before
+    // they are taken out of the DOM. Without timing, nodes are inserted and removed right away.
+    pub fn add_transition(&mut self, node: &VRef, transition: Transition) {
+        self.transitions.registered.insert(*node, transition);
+    }
+
+    pub fn remove_transition(&mut self, node: &VRef) {
+        self.transitions.registered.remove(node);
+    }
+
+    pub fn has_transition(&self, node: &VRef) -> bool {
+        self.transitions.registered.contains_key(node)
+    }
+
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.transitions.timing = Some(timing);
+    }
+
+    pub fn is_leaving(&self, node: &VRef) -> bool {
+        self.transitions.active.borrow().get(node).is_some_and(|running| running.phase == Phase::Leave)
+    }
+
+    pub(crate) fn enter(&mut self, node_ref: &VRef, node: &B::Node) {
+        self.start(node_ref, node, Phase::Enter);
+    }
+
+    // Returns whether the node is left in the DOM until its transition is done.
+    // The transition goes along with the leaving node, so it is not registered for a node which is gone.
+    pub(crate) fn leave(&mut self, node_ref: &VRef, node: &B::Node) -> bool {
+        let leaving = self.start(node_ref, node, Phase::Leave);
+        self.transitions.registered.remove(node_ref);
+        leaving
+    }
+
+    // A node which is inserted again while it is still leaving is replaced by a new one,
+    // so the leaving one is taken out right away and its transition registered again.
+    pub(crate) fn cancel_leave(&mut self, node_ref: &VRef) {
+        let transition = self.transitions.active.borrow().get(node_ref)
+            .filter(|running| running.phase == Phase::Leave)
+            .map(|running| running.transition.clone());
+        if let Some(transition) = transition {
+            self.transitions.registered.entry(*node_ref).or_insert(transition);
+            finish(&self.transitions.active, &self.backend, self.transitions.timing.as_ref(), node_ref, None);
+        }
+    }
+
+    // For nodes released without a leave transition of their own.
+    pub(crate) fn forget_transition(&mut self, node_ref: &VRef) {
+        self.transitions.registered.remove(node_ref);
+    }
+
+    pub(crate) fn cancel_transitions(&mut self) {
+        let node_refs: Vec<VRef> = self.transitions.active.borrow().keys().cloned().collect();
+        node_refs.iter().for_each(|node_ref| {
+            finish(&self.transitions.active, &self.backend, self.transitions.timing.as_ref(), node_ref, None);
+        });
+    }
+
+    pub(crate) fn is_leaving_node(&self, node: &B::Node) -> bool {
+        self.transitions.active.borrow().values()
+            .any(|running| running.phase == Phase::Leave && self.backend.is_same(&running.node, node))
+    }
+
+    pub(crate) fn is_inside_leaving_node(&self, node: &B::Node) -> bool {
+        let mut current = self.backend.parent(node);
+        while let Some(node) = current {
+            if self.is_leaving_node(&node) {
+                return true
+            }
+            current = self.backend.parent(&node);
+        }
+        false
+    }
+
+    // Re-rendering resets the class attribute, which would drop the classes of a running enter transition.
+    pub(crate) fn reapply_transitions(&self) {
+        self.transitions.active.borrow().values()
+            .filter(|running| running.phase == Phase::Enter)
+            .for_each(|running| running.classes.iter().for_each(|class| add_class(&self.backend, &running.node, class)));
+    }
+
+    fn start(&mut self, node_ref: &VRef, node: &B::Node, phase: Phase) -> bool {
+        let (transition, timing) = match (self.transitions.registered.get(node_ref), &self.transitions.timing) {
+            (Some(transition), Some(timing)) => (transition.clone(), timing.clone()),
+            _ => return false,
+        };
+        if self.transitions.active.borrow().contains_key(node_ref) {
+            finish(&self.transitions.active, &self.backend, Some(&timing), node_ref, None);
+        }
+        let id = self.transitions.next_id;
+        self.transitions.next_id += 1;
+
+        let classes = vec![transition.class(phase, "from"), transition.class(phase, "active")];
+        classes.iter().for_each(|class| add_class(&self.backend, node, class));
+        let subscriptions = ["transitionend", "animationend"].iter()
+            .filter_map(|event| {
+                let callback: Callback<B::Event> = {
+                    let active = Rc::downgrade(&self.transitions.active);
+                    let backend = self.backend.clone();
+                    let timing = timing.clone();
+                    let node = node.clone();
+                    let node_ref = *node_ref;
+                    // Transitions of descendants bubble up as well.
+                    Rc::new(move |event: &B::Event| {
+                        if backend.event_target(event).is_some_and(|target| backend.is_same(&target, &node)) {
+                            finish_weak(&active, &backend, &timing, &node_ref, id);
+                        }
+                    })
+                };
+                self.backend.listen(node, event, false, callback)
+                    .map_err(|cause| warn!("Failed to listen for the end of transition {}: {}", transition.name, cause))
+                    .ok()
+            })
+            .collect();
+        self.transitions.active.borrow_mut().insert(*node_ref, Running {
+            id,
+            phase,
+            node: node.clone(),
+            transition: transition.clone(),
+            classes,
+            subscriptions,
+        });
+
+        // The from classes have to be rendered for a frame, so the browser has a style to start the transition from.
+        let advance: Task = {
+            let active = Rc::downgrade(&self.transitions.active);
+            let backend = self.backend.clone();
+            let frames = timing.frames.clone();
+            let node_ref = *node_ref;
+            Box::new(move || {
+                let requested = frames.request_frame(Box::new(move || advance(&active, &backend, &node_ref, id)));
+                if let Err(cause) = requested {
+                    warn!("Failed to request a frame for a transition: {}", cause);
+                }
+            })
+        };
+        if let Err(cause) = timing.frames.request_frame(advance) {
+            warn!("Failed to request a frame for a transition: {}", cause);
+        }
+        let timeout: Task = {
+            let active = Rc::downgrade(&self.transitions.active);
+            let backend = self.backend.clone();
+            let timing = timing.clone();
+            let node_ref = *node_ref;
+            Box::new(move || finish_weak(&active, &backend, &timing, &node_ref, id))
+        };
+        if let Err(cause) = timing.timer.set_timeout(transition.timeout, timeout) {
+            warn!("Failed to set the timeout of transition {}, ending it right away: {}", transition.name, cause);
+            finish(&self.transitions.active, &self.backend, Some(&timing), node_ref, Some(id));
+        }
+        true
+    }
+}
+
+fn advance<B: Backend>(active: &Weak<Registry<Counted<B>>>, backend: &Counted<B>, node_ref: &VRef, id: usize) {
+    let active = match active.upgrade() {
+        Some(active) => active,
+        None => return,
+    };
+    let mut active = active.borrow_mut();
+    if let Some(running) = active.get_mut(node_ref).filter(|running| running.id == id) {
+        let from = running.transition.class(running.phase, "from");
+        let to = running.transition.class(running.phase, "to");
+        remove_class(backend, &running.node, &from);
+        add_class(backend, &running.node, &to);
+        running.classes.retain(|class| *class != from);
+        running.classes.push(to);
+    }
+}
+
+fn finish_weak<B: Backend>(active: &Weak<Registry<Counted<B>>>, backend: &Counted<B>, timing: &Timing, node_ref: &VRef, id: usize) {
+    if let Some(active) = active.upgrade() {
+        finish(&active, backend, Some(timing), node_ref, Some(id));
+    }
+}
+
+// Ends the running transition of the node, if it is the one with the given id.
+fn finish<B: Backend>(active: &Active<Counted<B>>, backend: &Counted<B>, timing: Option<&Timing>, node_ref: &VRef, id: Option<usize>) {
+    let running = {
+        let mut active = active.borrow_mut();
+        match active.get(node_ref) {
+            Some(running) if id.is_none_or(|id| running.id == id) => active.remove(node_ref),
+            _ => None,
+        }
+    };
+    let running = match running {
+        Some(running) => running,
+        None => return,
+    };
+    running.classes.iter().for_each(|class| remove_class(backend, &running.node, class));
+    if running.phase == Phase::Leave {
+        if let Err(error) = detach(backend, node_ref, &running.node) {
+            warn!("Failed to remove a node after its transition: {}", error);
+        }
+    }
+    // This may run inside one of the end listeners, which must not be dropped while they are running.
+    let subscriptions = running.subscriptions;
+    let released = timing.map(|timing| timing.timer.set_timeout(0.0, Box::new(move || drop(subscriptions))));
+    if let Some(Err(cause)) = released {
+        warn!("Failed to release the listeners of a transition: {}", cause);
+    }
+}
+
+fn add_class<B: Backend>(backend: &B, node: &B::Node, class: &str) {
+    let classes = backend.get_attribute(node, "class").unwrap_or_default();
+    if !classes.split_whitespace().any(|existing| existing == class) {
+        let value = classes.split_whitespace().chain([class]).collect::<Vec<&str>>().join(" ");
+        if let Err(cause) = backend.set_attribute(node, "class", value.as_str()) {
+            warn!("Failed to add transition class {}: {}", class, cause);
+        }
+    }
+}
+
+fn remove_class<B: Backend>(backend: &B, node: &B::Node, class: &str) {
+    if let Some(classes) = backend.get_attribute(node, "class") {
+        if classes.split_whitespace().any(|existing| existing == class) {
+            let value = classes.split_whitespace().filter(|existing| *existing != class).collect::<Vec<&str>>().join(" ");
+            let updated = match value.is_empty() {
+                true => backend.remove_attribute(node, "class"),
+                false => backend.set_attribute(node, "class", value.as_str()),
+            };
+            if let Err(cause) = updated {
+                warn!("Failed to remove transition class {}: {}", class, cause);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use vdom::VTree;
+    use vdom::testing::{element, text};
+
+    use crate::{MountOptions, Timing, Transition};
+    use crate::testing::{html, memory_mount, ManualFrames, ManualTimer};
+
+    #[test]
+    fn test_enter_and_leave() {
+        let frames = ManualFrames::default();
+        let timer = ManualTimer::default();
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.set_timing(Timing::new(frames.clone(), timer.clone()));
+        mount.update(&tree).expect("mounted");
+
+        let mut inserted = tree.clone();
+        let item = element(&mut inserted, Some(&list), "li", &[("class", "item")]);
+        text(&mut inserted, &item, "Hello");
+        mount.add_transition(&item, Transition::new("fade", 300.0));
+        mount.update(&inserted).expect("inserted");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"item fade-enter-from fade-enter-active\">Hello</li></ul>"));
+
+        frames.tick();
+        frames.tick();
+        mount.update(&inserted).expect("rendered again");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"item fade-enter-active fade-enter-to\">Hello</li></ul>"));
+
+        mount.get(&item).expect("item mounted").dispatch("transitionend");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"item\">Hello</li></ul>"));
+
+        mount.update(&tree).expect("removed");
+
+        assert_that!(mount.is_leaving(&item)).is_true();
+        assert_that!(mount.get(&item)).is_none();
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"item fade-leave-from fade-leave-active\">Hello</li></ul>"));
+
+        frames.tick();
+        frames.tick();
+        timer.run();
+
+        assert_that!(mount.is_leaving(&item)).is_false();
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul></ul>"));
+        assert_that!(mount.transitions.registered.is_empty()).is_true();
+    }
+
+    #[test]
+    fn test_forget_released_transitions() {
+        let frames = ManualFrames::default();
+        let timer = ManualTimer::default();
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let item = element(&mut tree, Some(&list), "li", &[]);
+        let label = element(&mut tree, Some(&item), "span", &[]);
+        let mut mount = memory_mount(MountOptions::default());
+        mount.set_timing(Timing::new(frames.clone(), timer.clone()));
+        for _ in 0..3 {
+            mount.add_transition(&list, Transition::new("fade", 300.0));
+            mount.add_transition(&item, Transition::new("fade", 300.0));
+            mount.update(&tree).expect("mounted");
+            mount.unmount().expect("unmounted");
+            timer.run();
+        }
+
+        assert_that!(mount.transitions.registered.is_empty()).is_true();
+
+        mount.update(&tree).expect("mounted");
+        mount.add_transition(&label, Transition::new("fade", 300.0));
+        let mut removed = tree.clone();
+        removed.remove_node(&item);
+        mount.patch(&tree, &removed).expect("removed");
+        timer.run();
+
+        assert_that!(mount.is_leaving(&item)).is_false();
+        assert_that!(mount.transitions.registered.is_empty()).is_true();
+    }
+
+    #[test]
+    fn test_reinsert_while_leaving() {
+        let frames = ManualFrames::default();
+        let timer = ManualTimer::default();
+        let mut tree = VTree::new();
+        let list = element(&mut tree, None, "ul", &[]);
+        let first = element(&mut tree, Some(&list), "li", &[]);
+        text(&mut tree, &first, "First");
+        let mut mount = memory_mount(MountOptions::default());
+        mount.set_timing(Timing::new(frames.clone(), timer.clone()));
+        mount.update(&tree).expect("mounted");
+
+        let mut removed = tree.clone();
+        removed.remove_node(&first);
+        let second = element(&mut removed, Some(&list), "li", &[]);
+        text(&mut removed, &second, "Second");
+        mount.add_transition(&first, Transition::new("slide", 300.0));
+        mount.update(&removed).expect("removed");
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"slide-leave-from slide-leave-active\">First</li><li>Second</li></ul>"));
+
+        let leaving = mount.container().children()[0].children()[0].clone();
+        mount.update(&tree).expect("inserted again");
+
+        assert_that!(mount.is_leaving(&first)).is_false();
+        assert_that!(leaving.parent()).is_none();
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li class=\"slide-enter-from slide-enter-active\">First</li></ul>"));
+
+        timer.run();
+
+        assert_that!(html(&mount)).is_equal_to(String::from("<ul><li>First</li></ul>"));
+    }
+}
@@ -0,0 +1,416 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::rc::Rc;
+
+use vdom::{Listener, VItem, VRef, VTree};
+
+use crate::{Backend, Mount};
+
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum RowHeight {
+    Fixed(f64),
+    // Rows are laid out with the estimate until they have been measured.
+    Measured {
+        estimate: f64,
+    },
+}
+
+// The refs of a rendered row. Rows scrolled out of view hand their slot over to the rows
+// scrolled into view, so the DOM nodes behind the refs are updated instead of recreated.
+struct Slot {
+    row: VRef,
+    refs: Vec<VRef>,
+}
+
+pub struct Row<'a> {
+    index: usize,
+    slot: &'a mut Slot,
+}
+
+impl Row<'_> {
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn node(&self) -> VRef {
+        self.slot.row
+    }
+
+    // Refs for the content of the row which stay the same for as long as the slot is reused.
+    pub fn node_ref(&mut self, position: usize) -> VRef {
+        while self.slot.refs.len() <= position {
+            self.slot.refs.push(VRef::random());
+        }
+        self.slot.refs[position]
+    }
+}
+
+pub struct VirtualList {
+    container: VRef,
+    spacer: VRef,
+    row_count: usize,
+    heights: RowHeight,
+    measured: Vec<Option<f64>>,
+    viewport: f64,
+    scroll_top: f64,
+    overscan: usize,
+    slots: Vec<Slot>,
+    // Maps the index of each rendered row to its slot.
+    assigned: HashMap<usize, usize>,
+    attributes: Vec<(String, String)>,
+    listener: Option<Listener>,
+}
+
+impl VirtualList {
+
+    pub fn new(row_count: usize, heights: RowHeight) -> VirtualList {
+        VirtualList {
+            container: VRef::random(),
+            spacer: VRef::random(),
+            row_count,
+            heights,
+            measured: vec![None; row_count],
+            viewport: 0.0,
+            scroll_top: 0.0,
+            overscan: 3,
+            slots: Vec::new(),
+            assigned: HashMap::new(),
+            attributes: Vec::new(),
+            listener: None,
+        }
+    }
+
+    // Syncs the list on every scroll of its container and calls back if it has to be rendered
+    // again, e.g. to invalidate a scheduled render.
+    pub fn listen<B, F>(list: &Rc<RefCell<VirtualList>>, mount: &Rc<RefCell<Mount<B>>>, changed: F) where B: Backend, F: Fn() + 'static {
+        let weak_list = Rc::downgrade(list);
+        let weak_mount = Rc::downgrade(mount);
+        let listener = Listener::new("scroll", move |_| {
+            if let (Some(list), Some(mount)) = (weak_list.upgrade(), weak_mount.upgrade()) {
+                let synced = match (list.try_borrow_mut(), mount.try_borrow()) {
+                    (Ok(mut list), Ok(mount)) => list.sync(&mount),
+                    _ => false,
+                };
+                if synced {
+                    changed();
+                }
+            }
+        });
+        list.borrow_mut().listener = Some(listener);
+    }
+
+    pub fn container(&self) -> VRef {
+        self.container
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    pub fn set_row_count(&mut self, row_count: usize) {
+        self.row_count = row_count;
+        self.measured.resize(row_count, None);
+        self.assigned.retain(|index, _| *index < row_count);
+    }
+
+    // Also the height of the container, unless a style or class of its own sets one.
+    pub fn set_viewport(&mut self, viewport: f64) {
+        self.viewport = viewport;
+    }
+
+    // Attributes of the container, a style is added to the one of the list.
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        self.attributes.retain(|(attribute, _)| attribute != name);
+        self.attributes.push((String::from(name), String::from(value)));
+    }
+
+    pub fn set_scroll_top(&mut self, scroll_top: f64) {
+        self.scroll_top = scroll_top;
+    }
+
+    pub fn set_overscan(&mut self, overscan: usize) {
+        self.overscan = overscan;
+    }
+
+    // Returns whether the height of the row changed, which moves all rows below it.
+    pub fn measure(&mut self, index: usize, height: f64) -> bool {
+        match (self.heights, self.measured.get_mut(index)) {
+            (RowHeight::Measured { .. }, Some(measured)) if *measured != Some(height) => {
+                *measured = Some(height);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn height(&self, index: usize) -> f64 {
+        match self.heights {
+            RowHeight::Fixed(height) => height,
+            RowHeight::Measured { estimate } => self.measured.get(index).copied().flatten().unwrap_or(estimate),
+        }
+    }
+
+    pub fn offset(&self, index: usize) -> f64 {
+        match self.heights {
+            RowHeight::Fixed(height) => height * index.min(self.row_count) as f64,
+            RowHeight::Measured { .. } => (0..index.min(self.row_count)).map(|index| self.height(index)).sum(),
+        }
+    }
+
+    pub fn total_height(&self) -> f64 {
+        self.offset(self.row_count)
+    }
+
+    // The rows intersecting the viewport, plus the overscan on either side.
+    pub fn visible(&self) -> Range<usize> {
+        let bottom = self.scroll_top + self.viewport;
+        let (first, last) = match self.heights {
+            RowHeight::Fixed(height) if height > 0.0 => {
+                ((self.scroll_top / height).floor() as usize, (bottom / height).ceil() as usize)
+            }
+            _ => {
+                let mut first = None;
+                let mut top = 0.0;
+                let mut index = 0;
+                while index < self.row_count && top < bottom {
+                    top += self.height(index);
+                    if first.is_none() && top > self.scroll_top {
+                        first = Some(index);
+                    }
+                    index += 1;
+                }
+                (first.unwrap_or(index), index)
+            }
+        };
+        let first = first.min(self.row_count);
+        first.saturating_sub(self.overscan)..(last + self.overscan).min(self.row_count)
+    }
+
+    // Adds the list to the tree, calling back for every visible row to fill in its content.
+    pub fn render<F>(&mut self, tree: &mut VTree, parent: Option<&VRef>, mut row: F) -> VRef where F: FnMut(&mut VTree, &mut Row) {
+        let visible = self.visible();
+        self.assigned.retain(|index, _| visible.contains(index));
+        let mut free: Vec<usize> = (0..self.slots.len())
+            .filter(|slot| !self.assigned.values().any(|assigned| assigned == slot))
+            .rev()
+            .collect();
+        for index in visible {
+            if !self.assigned.contains_key(&index) {
+                let slot = free.pop().unwrap_or_else(|| {
+                    self.slots.push(Slot { row: VRef::random(), refs: Vec::new() });
+                    self.slots.len() - 1
+                });
+                self.assigned.insert(index, slot);
+            }
+        }
+
+        let mut style = String::from("overflow-y: auto;");
+        if self.viewport > 0.0 {
+            style.push_str(format!(" height: {}px;", self.viewport).as_str());
+        }
+        let mut attributes = vec![(String::from("style"), style)];
+        for (name, value) in self.attributes.iter() {
+            match name.as_str() {
+                "style" => {
+                    attributes[0].1.push(' ');
+                    attributes[0].1.push_str(value);
+                }
+                _ => attributes.push((name.clone(), value.clone())),
+            }
+        }
+        element(tree, &self.container, attributes, self.listener.iter().cloned().collect());
+        match parent {
+            Some(parent) => tree.append_child(parent, &self.container),
+            None => tree.set_root(&self.container),
+        }
+        element(tree, &self.spacer, vec![(String::from("style"), format!("position: relative; height: {}px;", self.total_height()))], Vec::new());
+        tree.append_child(&self.container, &self.spacer);
+
+        // Rows keep the DOM order of their slots, so recycling one never moves it.
+        let mut rows: Vec<(usize, usize)> = self.assigned.iter().map(|(index, slot)| (*slot, *index)).collect();
+        rows.sort_unstable();
+        for (slot, index) in rows {
+            let mut style = format!("position: absolute; top: {}px; left: 0; right: 0;", self.offset(index));
+            if let RowHeight::Fixed(height) = self.heights {
+                style.push_str(format!(" height: {}px;", height).as_str());
+            }
+            let node = self.slots[slot].row;
+            element(tree, &node, vec![(String::from("style"), style), (String::from("data-index"), index.to_string())], Vec::new());
+            tree.append_child(&self.spacer, &node);
+            row(tree, &mut Row { index, slot: &mut self.slots[slot] });
+        }
+        self.container
+    }
+
+    // Reads the scroll offset and height of the container and the heights of the rendered rows.
+    // Returns whether the list has to be rendered again.
+    pub fn sync<B: Backend>(&mut self, mount: &Mount<B>) -> bool {
+        let visible = self.visible();
+        let backend = mount.backend();
+        if let Some(container) = mount.get(&self.container) {
+            self.scroll_top = backend.scroll(container).1 as f64;
+            // Before the first layout the container has no height yet.
+            let height = backend.height(container);
+            if height > 0.0 {
+                self.viewport = height;
+            }
+        }
+        let mut measured = false;
+        if let RowHeight::Measured { .. } = self.heights {
+            let rows: Vec<(usize, VRef)> = self.assigned.iter().map(|(index, slot)| (*index, self.slots[*slot].row)).collect();
+            for (index, row) in rows {
+                // Rows without a height have not been laid out yet.
+                let height = mount.get(&row).map(|node| backend.height(node)).unwrap_or_default();
+                if height > 0.0 {
+                    measured |= self.measure(index, height);
+                }
+            }
+        }
+        measured || self.visible() != visible
+    }
+}
+
+fn element(tree: &mut VTree, node: &VRef, attributes: Vec<(String, String)>, listeners: Vec<Listener>) {
+    tree.create_node(node);
+    tree.update_node(node, Box::new(|node| {
+        node.item = Some(VItem::Element { name: String::from("div"), attributes, listeners })
+    }));
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use vdom::{VItem, VTree};
+
+    use crate::{Backend, MemoryBackend, Mount, MountOptions, RowHeight, VirtualList};
+    use crate::testing::memory_mount;
+
+    fn render(list: &mut VirtualList, mount: &mut Mount<MemoryBackend>) {
+        let mut tree = VTree::new();
+        list.render(&mut tree, None, |tree, row| {
+            let label = row.node_ref(0);
+            let value = format!("Row {}", row.index());
+            tree.create_node(&label);
+            tree.update_node(&label, Box::new(|node| node.item = Some(VItem::Text { value })));
+            tree.append_child(&row.node(), &label);
+        });
+        mount.update(&tree).expect("rendered");
+    }
+
+    fn rows(mount: &Mount<MemoryBackend>) -> Vec<String> {
+        let spacer = mount.root().and_then(|root| root.children().first().cloned()).expect("spacer mounted");
+        spacer.children().iter()
+            .map(|row| row.children().iter().filter_map(|label| label.value()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_visible() {
+        let mut list = VirtualList::new(1000, RowHeight::Fixed(20.0));
+        list.set_viewport(100.0);
+        list.set_overscan(2);
+
+        assert_that!(list.visible()).is_equal_to(0..7);
+        assert_that!(list.total_height()).is_equal_to(20000.0);
+
+        list.set_scroll_top(410.0);
+
+        assert_that!(list.visible()).is_equal_to(18..28);
+
+        list.set_scroll_top(19990.0);
+
+        assert_that!(list.visible()).is_equal_to(997..1000);
+
+        let mut measured = VirtualList::new(100, RowHeight::Measured { estimate: 20.0 });
+        measured.set_viewport(100.0);
+        measured.set_overscan(0);
+        measured.measure(0, 60.0);
+        measured.measure(1, 60.0);
+
+        assert_that!(measured.visible()).is_equal_to(0..2);
+        assert_that!(measured.offset(3)).is_equal_to(140.0);
+    }
+
+    #[test]
+    fn test_recycling() {
+        let mut mount = memory_mount(MountOptions::default());
+        let mut list = VirtualList::new(1000, RowHeight::Fixed(20.0));
+        list.set_viewport(60.0);
+        list.set_overscan(0);
+        render(&mut list, &mut mount);
+
+        assert_that!(rows(&mount)).is_equal_to(vec![String::from("Row 0"), String::from("Row 1"), String::from("Row 2")]);
+
+        let container = mount.get(&list.container()).cloned().expect("container mounted");
+        let first = mount.root().and_then(|root| root.children()[0].children().first().cloned()).expect("row mounted");
+        container.set_height(60.0);
+        mount.backend().set_scroll(&container, (0, 40));
+
+        assert_that!(list.sync(&mount)).is_true();
+
+        mount.reset_operations();
+        render(&mut list, &mut mount);
+
+        assert_that!(rows(&mount)).is_equal_to(vec![String::from("Row 3"), String::from("Row 4"), String::from("Row 2")]);
+        assert_that!(mount.operations().created).is_equal_to(0);
+        assert_that!(mount.operations().removed).is_equal_to(0);
+        assert_that!(first.attribute("data-index")).is_equal_to(Some(String::from("3")));
+        assert_that!(first.attribute("style")).is_equal_to(Some(String::from("position: absolute; top: 60px; left: 0; right: 0; height: 20px;")));
+        assert_that!(list.sync(&mount)).is_false();
+    }
+
+    #[test]
+    fn test_measured_rows() {
+        let mut mount = memory_mount(MountOptions::default());
+        let mut list = VirtualList::new(100, RowHeight::Measured { estimate: 20.0 });
+        list.set_viewport(60.0);
+        list.set_overscan(0);
+        render(&mut list, &mut mount);
+        let spacer = mount.root().and_then(|root| root.children().first().cloned()).expect("spacer mounted");
+        spacer.children().iter().for_each(|row| row.set_height(40.0));
+        mount.get(&list.container()).expect("container mounted").set_height(60.0);
+
+        assert_that!(list.sync(&mount)).is_true();
+
+        render(&mut list, &mut mount);
+
+        assert_that!(rows(&mount)).is_equal_to(vec![String::from("Row 0"), String::from("Row 1")]);
+        assert_that!(spacer.attribute("style")).is_equal_to(Some(String::from("position: relative; height: 2060px;")));
+    }
+
+    #[test]
+    fn test_scroll_listener() {
+        let mount = Rc::new(RefCell::new(memory_mount(MountOptions::default())));
+        let list = Rc::new(RefCell::new(VirtualList::new(1000, RowHeight::Fixed(20.0))));
+        let renders = Rc::new(Cell::new(0));
+        {
+            let renders = renders.clone();
+            VirtualList::listen(&list, &mount, move || renders.set(renders.get() + 1));
+        }
+        list.borrow_mut().set_viewport(60.0);
+        list.borrow_mut().set_overscan(0);
+        list.borrow_mut().set_attribute("class", "table");
+        list.borrow_mut().set_attribute("style", "border: none;");
+        render(&mut list.borrow_mut(), &mut mount.borrow_mut());
+
+        let container = mount.borrow().get(&list.borrow().container()).cloned().expect("container mounted");
+        assert_that!(container.attribute("style")).is_equal_to(Some(String::from("overflow-y: auto; height: 60px; border: none;")));
+        assert_that!(container.attribute("class")).is_equal_to(Some(String::from("table")));
+
+        container.dispatch("scroll");
+
+        assert_that!(renders.get()).is_equal_to(0);
+
+        mount.borrow().backend().set_scroll(&container, (0, 100));
+        container.dispatch("scroll");
+
+        assert_that!(renders.get()).is_equal_to(1);
+        assert_that!(list.borrow().visible()).is_equal_to(5..8);
+    }
+}
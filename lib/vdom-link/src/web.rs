@@ -0,0 +1,242 @@
+use js_sys::Reflect;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{Document, Element, Event, HtmlElement, HtmlInputElement, HtmlTextAreaElement, Node, ShadowRoot, Text};
+
+use vdom::VRef;
+
+use crate::{Backend, Callback, Cause, Error, Property, Selection};
+
+// Expando property through which a DOM node is mapped back to its VRef.
+const VREF_PROPERTY: &str = "__vdomRef";
+
+#[derive(Clone)]
+pub struct WebBackend {
+    document: Document,
+    // The document only sees the host of a shadow root as focused, the root knows which of its nodes is.
+    shadow_root: Option<ShadowRoot>,
+}
+
+impl WebBackend {
+
+    pub fn new(document: &Document) -> WebBackend {
+        WebBackend { document: document.clone(), shadow_root: None }
+    }
+
+    pub fn for_element(element: &Element) -> Result<WebBackend, Error> {
+        element.owner_document()
+            .map(|document| WebBackend::new(&document))
+            .ok_or(Error::MissingDocument)
+    }
+
+    pub fn for_shadow_root(shadow_root: &ShadowRoot) -> Result<WebBackend, Error> {
+        shadow_root.owner_document()
+            .map(|document| WebBackend { document, shadow_root: Some(shadow_root.clone()) })
+            .ok_or(Error::MissingDocument)
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+}
+
+pub struct WebSubscription {
+    target: Node,
+    event: String,
+    capture: bool,
+    closure: Closure<dyn FnMut(Event)>,
+}
+
+impl Drop for WebSubscription {
+
+    fn drop(&mut self) {
+        let _ = self.target.remove_event_listener_with_callback_and_bool(self.event.as_str(), self.closure.as_ref().unchecked_ref(), self.capture);
+    }
+}
+
+impl Backend for WebBackend {
+
+    type Node = Node;
+    type Event = Event;
+    type Subscription = WebSubscription;
+
+    fn create_element(&self, name: &str) -> Result<Node, Cause> {
+        Ok(self.document.create_element(name)?.into())
+    }
+
+    fn create_text(&self, value: &str) -> Result<Node, Cause> {
+        Ok(self.document.create_text_node(value).into())
+    }
+
+    fn tag_name(&self, node: &Node) -> Option<String> {
+        node.dyn_ref::<Element>().map(|element| element.tag_name())
+    }
+
+    fn text(&self, node: &Node) -> Option<String> {
+        node.dyn_ref::<Text>().map(|text| text.data())
+    }
+
+    fn set_text(&self, node: &Node, value: &str) {
+        if let Some(text) = node.dyn_ref::<Text>() {
+            text.set_data(value);
+        }
+    }
+
+    fn attribute_names(&self, node: &Node) -> Vec<String> {
+        node.dyn_ref::<Element>()
+            .map(|element| element.get_attribute_names().iter().filter_map(|name| name.as_string()).collect())
+            .unwrap_or_default()
+    }
+
+    fn get_attribute(&self, node: &Node, name: &str) -> Option<String> {
+        node.dyn_ref::<Element>().and_then(|element| element.get_attribute(name))
+    }
+
+    fn set_attribute(&self, node: &Node, name: &str, value: &str) -> Result<(), Cause> {
+        match node.dyn_ref::<Element>() {
+            Some(element) => Ok(element.set_attribute(name, value)?),
+            None => Err(Cause::Message(String::from("not an element"))),
+        }
+    }
+
+    fn remove_attribute(&self, node: &Node, name: &str) -> Result<(), Cause> {
+        match node.dyn_ref::<Element>() {
+            Some(element) => Ok(element.remove_attribute(name)?),
+            None => Err(Cause::Message(String::from("not an element"))),
+        }
+    }
+
+    fn set_property(&self, node: &Node, name: &str, value: Property) -> Result<(), Cause> {
+        let value = match value {
+            Property::Bool(value) => JsValue::from_bool(value),
+            Property::Text(value) => JsValue::from_str(value.as_str()),
+        };
+        Reflect::set(node, &JsValue::from_str(name), &value)?;
+        Ok(())
+    }
+
+    fn parent(&self, node: &Node) -> Option<Node> {
+        node.parent_node()
+    }
+
+    fn first_child(&self, node: &Node) -> Option<Node> {
+        node.first_child()
+    }
+
+    fn child_at(&self, node: &Node, index: usize) -> Option<Node> {
+        node.child_nodes().item(index as u32)
+    }
+
+    fn insert_before(&self, parent: &Node, node: &Node, before: Option<&Node>) -> Result<(), Cause> {
+        parent.insert_before(node, before)?;
+        Ok(())
+    }
+
+    fn remove_child(&self, parent: &Node, node: &Node) -> Result<(), Cause> {
+        parent.remove_child(node)?;
+        Ok(())
+    }
+
+    fn replace_child(&self, parent: &Node, node: &Node, old: &Node) -> Result<(), Cause> {
+        parent.replace_child(node, old)?;
+        Ok(())
+    }
+
+    fn is_same(&self, node: &Node, other: &Node) -> bool {
+        node.is_same_node(Some(other))
+    }
+
+    fn set_ref(&self, node: &Node, node_ref: &VRef) -> Result<(), Cause> {
+        Reflect::set(node, &JsValue::from_str(VREF_PROPERTY), &JsValue::from_str(String::from(node_ref).as_str()))?;
+        Ok(())
+    }
+
+    fn get_ref(&self, node: &Node) -> Option<VRef> {
+        Reflect::get(node, &JsValue::from_str(VREF_PROPERTY)).ok()
+            .and_then(|value| value.as_string())
+            .and_then(|value| VRef::from_string(value).ok())
+    }
+
+    fn active_element(&self) -> Option<Node> {
+        match &self.shadow_root {
+            Some(shadow_root) => shadow_root.active_element(),
+            None => self.document.active_element(),
+        }.map(|element| element.into())
+    }
+
+    fn focus(&self, node: &Node) {
+        if let Some(element) = node.dyn_ref::<HtmlElement>() {
+            let _ = element.focus();
+        }
+    }
+
+    fn selection(&self, node: &Node) -> Option<Selection> {
+        // Inputs like type="number" do not support selection and throw instead.
+        if let Some(input) = node.dyn_ref::<HtmlInputElement>() {
+            let start = input.selection_start().ok().flatten()?;
+            let end = input.selection_end().ok().flatten()?;
+            Some(Selection { start, end, direction: input.selection_direction().ok().flatten() })
+        } else if let Some(text_area) = node.dyn_ref::<HtmlTextAreaElement>() {
+            let start = text_area.selection_start().ok().flatten()?;
+            let end = text_area.selection_end().ok().flatten()?;
+            Some(Selection { start, end, direction: text_area.selection_direction().ok().flatten() })
+        } else {
+            None
+        }
+    }
+
+    fn set_selection(&self, node: &Node, selection: &Selection) {
+        let direction = selection.direction.as_deref().unwrap_or("none");
+        if let Some(input) = node.dyn_ref::<HtmlInputElement>() {
+            let _ = input.set_selection_range_with_direction(selection.start, selection.end, direction);
+        } else if let Some(text_area) = node.dyn_ref::<HtmlTextAreaElement>() {
+            let _ = text_area.set_selection_range_with_direction(selection.start, selection.end, direction);
+        }
+    }
+
+    fn scroll(&self, node: &Node) -> (i32, i32) {
+        node.dyn_ref::<Element>()
+            .map(|element| (element.scroll_left(), element.scroll_top()))
+            .unwrap_or_default()
+    }
+
+    fn set_scroll(&self, node: &Node, (left, top): (i32, i32)) {
+        if let Some(element) = node.dyn_ref::<Element>() {
+            element.set_scroll_left(left);
+            element.set_scroll_top(top);
+        }
+    }
+
+    fn height(&self, node: &Node) -> f64 {
+        node.dyn_ref::<HtmlElement>()
+            .map(|element| element.offset_height() as f64)
+            .unwrap_or_default()
+    }
+
+    fn listen(&self, node: &Node, event: &str, capture: bool, callback: Callback<Event>) -> Result<WebSubscription, Cause> {
+        let closure = Closure::wrap(Box::new(move |event: Event| callback(&event)) as Box<dyn FnMut(Event)>);
+        node.add_event_listener_with_callback_and_bool(event, closure.as_ref().unchecked_ref(), capture)?;
+        Ok(WebSubscription {
+            target: node.clone(),
+            event: String::from(event),
+            capture,
+            closure,
+        })
+    }
+
+    fn event_type(&self, event: &Event) -> String {
+        event.type_()
+    }
+
+    fn event_target(&self, event: &Event) -> Option<Node> {
+        event.target().and_then(|target| target.dyn_into::<Node>().ok())
+    }
+
+    fn event_bubbles(&self, event: &Event) -> bool {
+        event.bubbles()
+    }
+
+    fn propagation_stopped(&self, event: &Event) -> bool {
+        event.cancel_bubble()
+    }
+}
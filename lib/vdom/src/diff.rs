@@ -0,0 +1,426 @@
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+
+use crate::{Listener, VItem, VRef, VTree};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Patch {
+    Insert { parent: Option<VRef>, node: VRef, item: VItem, before: Option<VRef> },
+    Move { parent: Option<VRef>, node: VRef, before: Option<VRef> },
+    Remove { node: VRef, descendants: Vec<VRef> },
+    Replace { node: VRef, item: VItem },
+    SetAttribute { node: VRef, name: String, value: String },
+    RemoveAttribute { node: VRef, name: String },
+    SetText { node: VRef, value: String },
+    SetListener { node: VRef, listener: Listener },
+    RemoveListener { node: VRef, event: String },
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub struct DiffStats {
+    // Nodes present in both trees, whose items were compared.
+    pub compared: usize,
+    pub inserted: usize,
+    pub moved: usize,
+    // Removed nodes including their descendants.
+    pub removed: usize,
+    pub replaced: usize,
+    pub attributes: usize,
+    pub texts: usize,
+    pub listeners: usize,
+}
+
+impl DiffStats {
+
+    fn count(&mut self, patch: &Patch) {
+        match patch {
+            Patch::Insert { .. } => self.inserted += 1,
+            Patch::Move { .. } => self.moved += 1,
+            Patch::Remove { descendants, .. } => self.removed += 1 + descendants.len(),
+            Patch::Replace { .. } => self.replaced += 1,
+            Patch::SetAttribute { .. } | Patch::RemoveAttribute { .. } => self.attributes += 1,
+            Patch::SetText { .. } => self.texts += 1,
+            Patch::SetListener { .. } | Patch::RemoveListener { .. } => self.listeners += 1,
+        }
+    }
+}
+
+pub fn diff(old: &VTree, new: &VTree) -> Vec<Patch> {
+    diff_with_stats(old, new).0
+}
+
+pub fn diff_with_stats(old: &VTree, new: &VTree) -> (Vec<Patch>, DiffStats) {
+    let old_index = index(old);
+    let new_index = index(new);
+    let mut patches: Vec<Patch> = Vec::new();
+    let compared = Cell::new(0);
+
+    if let Some(root) = old.get_root().filter(|root| old_index.contains_key(root)) {
+        diff_removals(old, &new_index, &root, &mut patches);
+    }
+
+    if let Some(root) = new.get_root().filter(|root| new_index.contains_key(root)) {
+        let context = DiffContext { old, new, old_index: &old_index, compared: &compared };
+        match old_index.get(&root) {
+            None => {
+                patches.push(Patch::Insert { parent: None, node: root, item: item(new, &root), before: None });
+            }
+            Some(_) => {
+                if old.get_root() != Some(root) {
+                    patches.push(Patch::Move { parent: None, node: root, before: None });
+                }
+                context.diff_item(&root, &mut patches);
+            }
+        }
+        context.diff_children(&root, &mut patches);
+    }
+
+    let mut stats = DiffStats { compared: compared.get(), ..DiffStats::default() };
+    patches.iter().for_each(|patch| stats.count(patch));
+    (patches, stats)
+}
+
+struct DiffContext<'a> {
+    old: &'a VTree,
+    new: &'a VTree,
+    old_index: &'a HashMap<VRef, (Option<VRef>, usize)>,
+    compared: &'a Cell<usize>,
+}
+
+impl DiffContext<'_> {
+
+    fn diff_item(&self, node: &VRef, patches: &mut Vec<Patch>) {
+        self.compared.set(self.compared.get() + 1);
+        match (self.old.item(node), self.new.item(node)) {
+            (Some(VItem::Element { name: old_name, attributes: old_attributes, listeners: old_listeners }),
+             Some(VItem::Element { name: new_name, attributes: new_attributes, listeners: new_listeners })) if old_name == new_name => {
+                old_attributes.iter()
+                    .filter(|(name, _)| !new_attributes.iter().any(|(new_name, _)| new_name == name))
+                    .for_each(|(name, _)| {
+                        patches.push(Patch::RemoveAttribute { node: *node, name: name.clone() })
+                    });
+                new_attributes.iter()
+                    .filter(|attribute| !old_attributes.contains(attribute))
+                    .for_each(|(name, value)| {
+                        patches.push(Patch::SetAttribute { node: *node, name: name.clone(), value: value.clone() })
+                    });
+                old_listeners.iter()
+                    .filter(|old| !new_listeners.iter().any(|new| new.event() == old.event()))
+                    .for_each(|listener| {
+                        patches.push(Patch::RemoveListener { node: *node, event: String::from(listener.event()) })
+                    });
+                new_listeners.iter()
+                    .filter(|listener| !old_listeners.contains(listener))
+                    .for_each(|listener| {
+                        patches.push(Patch::SetListener { node: *node, listener: listener.clone() })
+                    });
+            }
+            (Some(VItem::Text { value: old_value }), Some(VItem::Text { value: new_value })) => {
+                if old_value != new_value {
+                    patches.push(Patch::SetText { node: *node, value: new_value.clone() })
+                }
+            }
+            (_, Some(item)) => {
+                patches.push(Patch::Replace { node: *node, item: item.clone() })
+            }
+            (_, None) => {}
+        }
+    }
+
+    fn diff_children(&self, parent: &VRef, patches: &mut Vec<Patch>) {
+        let children: Vec<VRef> = self.new.children(parent).iter()
+            .filter(|child| child.item.is_some())
+            .map(|child| child.id)
+            .collect();
+
+        // Children which stay below the same parent only need to be moved if they are not
+        // part of the longest run that already has the right relative order.
+        let kept: Vec<(usize, usize)> = children.iter().enumerate()
+            .filter_map(|(index, child)| {
+                match self.old_index.get(child) {
+                    Some((Some(old_parent), old_position)) if old_parent == parent => Some((index, *old_position)),
+                    _ => None,
+                }
+            })
+            .collect();
+        let stable: HashSet<usize> = longest_increasing_subsequence(&kept.iter().map(|(_, position)| *position).collect::<Vec<usize>>())
+            .into_iter()
+            .map(|index| kept[index].0)
+            .collect();
+
+        let mut before: Option<VRef> = None;
+        children.iter().enumerate().rev().for_each(|(index, child)| {
+            if !self.old_index.contains_key(child) {
+                patches.push(Patch::Insert { parent: Some(*parent), node: *child, item: item(self.new, child), before });
+            }
+            else if !stable.contains(&index) {
+                patches.push(Patch::Move { parent: Some(*parent), node: *child, before });
+            }
+            before = Some(*child);
+        });
+
+        children.iter().for_each(|child| {
+            if self.old_index.contains_key(child) {
+                self.diff_item(child, patches);
+            }
+            self.diff_children(child, patches);
+        });
+    }
+}
+
+fn diff_removals(old: &VTree, new_index: &HashMap<VRef, (Option<VRef>, usize)>, node: &VRef, patches: &mut Vec<Patch>) {
+    if new_index.contains_key(node) {
+        old.children(node).iter()
+            .filter(|child| child.item.is_some())
+            .for_each(|child| diff_removals(old, new_index, &child.id, patches));
+    }
+    else {
+        let mut descendants: Vec<VRef> = Vec::new();
+        let mut pending: Vec<VRef> = old.children(node).iter().map(|child| child.id).collect();
+        while let Some(descendant) = pending.pop() {
+            if new_index.contains_key(&descendant) {
+                // Nodes which survive in the new tree are kept out of the removal. They leave the DOM along
+                // with their old parent and are put back by the Move patches, which come after all removals.
+                diff_removals(old, new_index, &descendant, patches);
+            }
+            else {
+                descendants.push(descendant);
+                pending.extend(old.children(&descendant).iter().map(|child| child.id));
+            }
+        }
+        patches.push(Patch::Remove { node: *node, descendants });
+    }
+}
+
+fn index(tree: &VTree) -> HashMap<VRef, (Option<VRef>, usize)> {
+    let mut index = HashMap::new();
+    let mut pending: Vec<(VRef, Option<VRef>, usize)> = tree.get_root()
+        .filter(|root| tree.item(root).is_some())
+        .map(|root| vec![(root, None, 0)])
+        .unwrap_or_default();
+    while let Some((node, parent, position)) = pending.pop() {
+        index.insert(node, (parent, position));
+        tree.children(&node).iter()
+            .filter(|child| child.item.is_some())
+            .enumerate()
+            .for_each(|(position, child)| pending.push((child.id, Some(node), position)));
+    }
+    index
+}
+
+fn item(tree: &VTree, node: &VRef) -> VItem {
+    tree.item(node).cloned().expect("indexed nodes have an item")
+}
+
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = vec![None; values.len()];
+    values.iter().enumerate().for_each(|(index, value)| {
+        let position = tails.partition_point(|tail| values[*tail] < *value);
+        if position > 0 {
+            predecessors[index] = Some(tails[position - 1]);
+        }
+        if position == tails.len() {
+            tails.push(index);
+        }
+        else {
+            tails[position] = index;
+        }
+    });
+    let mut result = Vec::new();
+    let mut current = tails.last().cloned();
+    while let Some(index) = current {
+        result.push(index);
+        current = predecessors[index];
+    }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use crate::{diff, diff_with_stats, DiffStats, Listener, Patch, VItem, VRef, VTree};
+    use crate::diff::longest_increasing_subsequence;
+    use crate::testing::{element, text};
+
+    fn create_list(items: &[&str]) -> (VTree, VRef, Vec<VRef>) {
+        let mut tree = VTree::new();
+        let ul = element(&mut tree, None, "ul", &[]);
+        let children = items.iter()
+            .map(|value| {
+                let li = element(&mut tree, Some(&ul), "li", &[]);
+                text(&mut tree, &li, value);
+                li
+            })
+            .collect();
+        (tree, ul, children)
+    }
+
+    #[test]
+    fn test_diff_unchanged() {
+        let (tree, _, _) = create_list(&["a", "b"]);
+
+        assert_that!(diff(&tree, &tree.clone())).is_empty();
+    }
+
+    #[test]
+    fn test_diff_attributes_and_text() {
+        let mut old = VTree::new();
+        let div = element(&mut old, None, "div", &[("class", "a"), ("title", "t")]);
+        let hello = text(&mut old, &div, "Hello");
+
+        let mut new = old.clone();
+        new.item_mut(&div).unwrap().set_attribute("class", "b");
+        new.item_mut(&div).unwrap().remove_attribute("title");
+        new.item_mut(&div).unwrap().set_attribute("id", "main");
+        *new.item_mut(&hello).unwrap() = VItem::Text { value: String::from("World") };
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::RemoveAttribute { node: div, name: String::from("title") },
+                Patch::SetAttribute { node: div, name: String::from("class"), value: String::from("b") },
+                Patch::SetAttribute { node: div, name: String::from("id"), value: String::from("main") },
+                Patch::SetText { node: hello, value: String::from("World") },
+            ]);
+    }
+
+    #[test]
+    fn test_diff_listeners() {
+        let mut old = VTree::new();
+        let button = element(&mut old, None, "button", &[]);
+        let click = Listener::new("click", |_| {});
+        old.item_mut(&button).unwrap().add_listener(click.clone());
+        old.item_mut(&button).unwrap().add_listener(Listener::new("focus", |_| {}));
+
+        let mut new = old.clone();
+
+        assert_that!(diff(&old, &new)).is_empty();
+
+        let blur = Listener::new("blur", |_| {});
+        let other_click = Listener::new("click", |_| {});
+        *new.item_mut(&button).unwrap() = VItem::Element {
+            name: String::from("button"),
+            attributes: Vec::new(),
+            listeners: vec![other_click.clone(), blur.clone()],
+        };
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::RemoveListener { node: button, event: String::from("focus") },
+                Patch::SetListener { node: button, listener: other_click },
+                Patch::SetListener { node: button, listener: blur },
+            ]);
+    }
+
+    #[test]
+    fn test_diff_insert_and_remove() {
+        let (old, ul, children) = create_list(&["a", "b", "c"]);
+        let mut new = old.clone();
+        let b_text = new.children(&children[1])[0].id;
+        new.remove_node(&children[1]);
+        let d = element(&mut new, Some(&ul), "li", &[("class", "new")]);
+        let d_text = text(&mut new, &d, "d");
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::Remove { node: children[1], descendants: vec![b_text] },
+                Patch::Insert {
+                    parent: Some(ul),
+                    node: d,
+                    item: VItem::Element { name: String::from("li"), attributes: vec![(String::from("class"), String::from("new"))], listeners: Vec::new() },
+                    before: None,
+                },
+                Patch::Insert {
+                    parent: Some(d),
+                    node: d_text,
+                    item: VItem::Text { value: String::from("d") },
+                    before: None,
+                },
+            ]);
+    }
+
+    #[test]
+    fn test_diff_stats() {
+        let (old, ul, children) = create_list(&["a", "b", "c"]);
+        let mut new = old.clone();
+        new.remove_node(&children[1]);
+        new.item_mut(&children[0]).unwrap().set_attribute("class", "first");
+        let d = element(&mut new, Some(&ul), "li", &[]);
+        text(&mut new, &d, "d");
+
+        let (patches, stats) = diff_with_stats(&old, &new);
+
+        assert_that!(patches).has_length(4);
+        assert_that!(stats).is_equal_to(DiffStats { compared: 5, inserted: 2, removed: 2, attributes: 1, ..DiffStats::default() });
+    }
+
+    #[test]
+    fn test_diff_reorder_with_minimal_moves() {
+        let (old, ul, children) = create_list(&["a", "b", "c", "d"]);
+        let mut new = old.clone();
+        new.remove_child(&ul, &children[3]);
+        new.insert_before(&ul, &children[3], &children[0]);
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::Move { parent: Some(ul), node: children[3], before: Some(children[0]) },
+            ]);
+    }
+
+    #[test]
+    fn test_diff_move_between_parents() {
+        let mut old = VTree::new();
+        let div = element(&mut old, None, "div", &[]);
+        let left = element(&mut old, Some(&div), "section", &[]);
+        let right = element(&mut old, Some(&div), "section", &[]);
+        let p = element(&mut old, Some(&left), "p", &[]);
+
+        let mut new = old.clone();
+        new.append_child(&right, &p);
+        new.remove_node(&left);
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::Remove { node: left, descendants: Vec::new() },
+                Patch::Move { parent: Some(right), node: p, before: None },
+            ]);
+    }
+
+    #[test]
+    fn test_diff_replace_and_new_root() {
+        let mut old = VTree::new();
+        let div = element(&mut old, None, "div", &[]);
+        let span = element(&mut old, Some(&div), "span", &[]);
+
+        let mut new = old.clone();
+        *new.item_mut(&span).unwrap() = VItem::Element { name: String::from("b"), attributes: Vec::new(), listeners: Vec::new() };
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::Replace { node: span, item: VItem::Element { name: String::from("b"), attributes: Vec::new(), listeners: Vec::new() } },
+            ]);
+
+        let mut new = VTree::new();
+        let section = element(&mut new, None, "section", &[]);
+
+        assert_that!(diff(&old, &new))
+            .is_equal_to(vec![
+                Patch::Remove { node: div, descendants: vec![span] },
+                Patch::Insert {
+                    parent: None,
+                    node: section,
+                    item: VItem::Element { name: String::from("section"), attributes: Vec::new(), listeners: Vec::new() },
+                    before: None,
+                },
+            ]);
+    }
+
+    #[test]
+    fn test_longest_increasing_subsequence() {
+        assert_that!(longest_increasing_subsequence(&[])).is_empty();
+        assert_that!(longest_increasing_subsequence(&[3, 0, 1, 2])).is_equal_to(vec![1, 2, 3]);
+        assert_that!(longest_increasing_subsequence(&[0, 3, 1, 2, 4])).is_equal_to(vec![0, 2, 3, 4]);
+    }
+}
@@ -2,6 +2,20 @@ use std::collections::HashMap;
 
 use uuid::Uuid;
 
+mod diff;
+mod listener;
+mod print;
+mod sanitize;
+mod visit;
+
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+pub use diff::{diff, diff_with_stats, DiffStats, Patch};
+pub use listener::{Handler, Listener};
+pub use sanitize::{Policy, Removal, Sanitizer};
+pub use visit::{Pipeline, Visit, Visitor, VisitorMut};
+
 pub struct Error;
 
 #[derive(Hash, PartialEq, Eq, Debug, Copy, Clone)]
@@ -39,8 +53,8 @@ impl From<&VRef> for String {
     }
 }
 
-impl From<&VNode> for VRef {
-    fn from(node: &VNode) -> Self {
+impl<T> From<&VNode<T>> for VRef {
+    fn from(node: &VNode<T>) -> Self {
         node.id
     }
 }
@@ -50,24 +64,72 @@ pub enum VItem {
     Element {
         name: String,
         attributes: Vec<(String, String)>,
-        text: Option<String>,
+        listeners: Vec<Listener>,
     },
     Text {
         value: String
     }
 }
 
+impl VItem {
+
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        match self {
+            VItem::Element { attributes, .. } => {
+                attributes.iter()
+                    .find(|(attribute, _)| attribute == name)
+                    .map(|(_, value)| value.as_str())
+            }
+            VItem::Text { .. } => None
+        }
+    }
+
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        if let VItem::Element { attributes, .. } = self {
+            match attributes.iter_mut().find(|(attribute, _)| attribute == name) {
+                Some((_, existing)) => *existing = String::from(value),
+                None => attributes.push((String::from(name), String::from(value))),
+            }
+        }
+    }
+
+    pub fn listeners(&self) -> &[Listener] {
+        match self {
+            VItem::Element { listeners, .. } => listeners.as_slice(),
+            VItem::Text { .. } => &[]
+        }
+    }
+
+    pub fn add_listener(&mut self, listener: Listener) {
+        if let VItem::Element { listeners, .. } = self {
+            listeners.retain(|existing| existing.event() != listener.event());
+            listeners.push(listener);
+        }
+    }
+
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        match self {
+            VItem::Element { attributes, .. } => {
+                attributes.iter()
+                    .position(|(attribute, _)| attribute == name)
+                    .map(|index| attributes.remove(index).1)
+            }
+            VItem::Text { .. } => None
+        }
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
-pub struct VNode {
+pub struct VNode<T = VItem> {
     pub id: VRef,
     pub parent: Option<VRef>,
     pub children: Vec<VRef>,
-    pub item: Option<VItem>,
+    pub item: Option<T>,
 }
 
-impl VNode {
+impl<T> VNode<T> {
 
-    pub fn new(id: VRef) -> VNode {
+    pub fn new(id: VRef) -> VNode<T> {
         VNode {
             id,
             parent: None,
@@ -77,17 +139,19 @@ impl VNode {
     }
 }
 
-#[derive(PartialEq, Debug)]
-pub struct VTree {
-    nodes: HashMap<VRef, VNode>,
+pub type UpdateFn<T> = Box<dyn FnOnce(&mut VNode<T>)>;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct VTree<T = VItem> {
+    nodes: HashMap<VRef, VNode<T>>,
     parents: HashMap<VRef, VRef>,
     children: HashMap<VRef, Vec<VRef>>,
     root: Option<VRef>
 }
 
-impl VTree {
-
-    pub fn new() -> VTree {
+// Trees of other payloads are created with VTree::default(), VTree::new() stays for VItem so that it can be inferred.
+impl<T> Default for VTree<T> {
+    fn default() -> Self {
         VTree {
             nodes: HashMap::new(),
             parents: HashMap::new(),
@@ -95,6 +159,16 @@ impl VTree {
             root: None
         }
     }
+}
+
+impl VTree<VItem> {
+
+    pub fn new() -> VTree {
+        VTree::default()
+    }
+}
+
+impl<T> VTree<T> {
 
     pub fn create_node(&mut self, node_ref: &VRef) -> VRef {
         let node = VNode::new(*node_ref);
@@ -122,7 +196,7 @@ impl VTree {
     pub fn append_child(&mut self, parent: &VRef, child: &VRef) {
 
         if let Some(previous_parent) = self.parents.remove(child) {
-            VTree::_remove_child(&mut self.children, &previous_parent, child);
+            Self::_remove_child(&mut self.children, &previous_parent, child);
         }
 
         self.parents.insert(*child, *parent);
@@ -153,15 +227,64 @@ impl VTree {
         }
     }
 
-    pub fn nodes(&self) -> Vec<&VNode> {
+    pub fn insert_before(&mut self, parent: &VRef, child: &VRef, reference: &VRef) {
+
+        if let Some(previous_parent) = self.parents.remove(child) {
+            Self::_remove_child(&mut self.children, &previous_parent, child);
+        }
+
+        self.parents.insert(*child, *parent);
+        let children = self.children.entry(*parent).or_default();
+        match children.iter().position(|vref| *vref == *reference) {
+            Some(index) => children.insert(index, *child),
+            None => children.push(*child),
+        }
+    }
+
+    pub fn remove_node(&mut self, node: &VRef) {
+
+        if let Some(parent) = self.parents.remove(node) {
+            Self::_remove_child(&mut self.children, &parent, node);
+            if self.children.get(&parent).is_some_and(|children| children.is_empty()) {
+                self.children.remove(&parent);
+            }
+        }
+
+        if self.root == Some(*node) {
+            self.root = None;
+        }
+
+        let mut pending = vec![*node];
+        while let Some(vref) = pending.pop() {
+            self.nodes.remove(&vref);
+            self.parents.remove(&vref);
+            if let Some(children) = self.children.remove(&vref) {
+                pending.extend(children);
+            }
+        }
+    }
+
+    pub fn contains_node(&self, node: &VRef) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    pub fn item(&self, node: &VRef) -> Option<&T> {
+        self.nodes.get(node).and_then(|node| node.item.as_ref())
+    }
+
+    pub fn item_mut(&mut self, node: &VRef) -> Option<&mut T> {
+        self.nodes.get_mut(node).and_then(|node| node.item.as_mut())
+    }
+
+    pub fn nodes(&self) -> Vec<&VNode<T>> {
         Vec::from_iter(self.nodes.values().into_iter())
     }
 
-    pub fn get_node(&self, node: &VRef) -> Option<VNode> {
+    pub fn get_node(&self, node: &VRef) -> Option<VNode<T>> where T: Clone {
         self.nodes.get(node).cloned()
     }
 
-    pub fn update_node(&mut self, node: &VRef, update_fn: Box<dyn FnOnce(&mut VNode) -> ()>) {
+    pub fn update_node(&mut self, node: &VRef, update_fn: UpdateFn<T>) {
         if let Some(node) = self.nodes.get_mut(node) {
             update_fn(node)
         }
@@ -171,7 +294,7 @@ impl VTree {
         self.parents.get(child)
     }
 
-    pub fn children(&self, parent: &VRef) -> Vec<&VNode> {
+    pub fn children(&self, parent: &VRef) -> Vec<&VNode<T>> {
         match self.children.get(parent) {
             None => {
                 Vec::new()
@@ -275,4 +398,111 @@ mod test {
             .is_equal_to(VItem::Text { value: String::from("div") })
 
     }
+
+    #[test]
+    fn test_insert_before() {
+
+        let mut tree = VTree::new();
+        let node_a = tree.create_random_node();
+        let node_b = tree.create_random_node();
+        let node_c = tree.create_random_node();
+        let node_d = tree.create_random_node();
+
+        tree.append_child(&node_a, &node_b);
+        tree.append_child(&node_a, &node_c);
+        tree.insert_before(&node_a, &node_d, &node_c);
+
+        assert_that!(&tree.children)
+            .contains_entry(node_a, vec![node_b, node_d, node_c]);
+
+        tree.insert_before(&node_a, &node_b, &node_a);
+
+        assert_that!(&tree.children)
+            .contains_entry(node_a, vec![node_d, node_c, node_b]);
+        assert_that!(&tree.parents)
+            .contains_entry(node_b, node_a);
+    }
+
+    #[test]
+    fn test_remove_node() {
+
+        let mut tree = VTree::new();
+        let node_a = tree.create_random_node();
+        let node_b = tree.create_random_node();
+        let node_c = tree.create_random_node();
+        let node_d = tree.create_random_node();
+
+        tree.set_root(&node_a);
+        tree.append_child(&node_a, &node_b);
+        tree.append_child(&node_a, &node_c);
+        tree.append_child(&node_b, &node_d);
+
+        tree.remove_node(&node_b);
+
+        assert_that!(&tree.nodes).has_length(2);
+        assert_that!(tree.contains_node(&node_d)).is_false();
+        assert_that!(&tree.parents).does_not_contain_key(node_d);
+        assert_that!(&tree.children)
+            .contains_entry(node_a, vec![node_c]);
+
+        tree.remove_node(&node_a);
+
+        assert_that!(&tree.nodes).is_empty();
+        assert_that!(&tree.parents).is_empty();
+        assert_that!(&tree.children).is_empty();
+        assert_that!(tree.get_root()).is_none();
+    }
+
+    #[test]
+    fn test_item_attributes() {
+
+        let mut item = VItem::Element {
+            name: String::from("div"),
+            attributes: vec![(String::from("class"), String::from("box"))],
+            listeners: Vec::new(),
+        };
+
+        item.set_attribute("id", "main");
+        item.set_attribute("class", "columns");
+
+        assert_that!(item.attribute("class")).is_equal_to(Some("columns"));
+        assert_that!(item.attribute("id")).is_equal_to(Some("main"));
+        assert_that!(item.remove_attribute("class")).is_equal_to(Some(String::from("columns")));
+        assert_that!(item.attribute("class")).is_none();
+        assert_that!(&item).is_equal_to(VItem::Element {
+            name: String::from("div"),
+            attributes: vec![(String::from("id"), String::from("main"))],
+            listeners: Vec::new(),
+        });
+    }
+
+    #[test]
+    fn test_custom_payload() {
+
+        #[derive(PartialEq, Debug, Clone)]
+        struct MenuEntry {
+            label: &'static str,
+        }
+
+        let mut tree: VTree<MenuEntry> = VTree::default();
+        let file = tree.create_random_node();
+        let open = tree.create_random_node();
+        let close = tree.create_random_node();
+
+        tree.set_root(&file);
+        tree.append_child(&file, &open);
+        tree.append_child(&file, &close);
+
+        tree.update_node(&file, Box::new(|node| node.item = Some(MenuEntry { label: "File" })));
+        tree.update_node(&open, Box::new(|node| node.item = Some(MenuEntry { label: "Open" })));
+        tree.update_node(&close, Box::new(|node| node.item = Some(MenuEntry { label: "Close" })));
+
+        assert_that!(tree.get_node(&file).unwrap().item)
+            .is_equal_to(Some(MenuEntry { label: "File" }));
+        assert_that!(tree.children(&file)
+            .iter()
+            .map(|node| node.item.clone().unwrap().label)
+            .collect::<Vec<&str>>())
+            .is_equal_to(vec!["Open", "Close"]);
+    }
 }
@@ -0,0 +1,75 @@
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+pub type Handler = Rc<dyn Fn(&dyn Any)>;
+
+#[derive(Clone)]
+pub struct Listener {
+    event: String,
+    handler: Handler,
+}
+
+impl Listener {
+
+    pub fn new<F>(event: &str, handler: F) -> Listener where F: Fn(&dyn Any) + 'static {
+        Listener { event: String::from(event), handler: Rc::new(handler) }
+    }
+
+    pub fn event(&self) -> &str {
+        self.event.as_str()
+    }
+
+    pub fn handler(&self) -> &Handler {
+        &self.handler
+    }
+
+    pub fn call(&self, event: &dyn Any) {
+        (self.handler)(event)
+    }
+}
+
+impl PartialEq for Listener {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.event == other.event && std::ptr::addr_eq(Rc::as_ptr(&self.handler), Rc::as_ptr(&other.handler))
+    }
+}
+
+impl Debug for Listener {
+
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        formatter.debug_struct("Listener")
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use crate::Listener;
+
+    #[test]
+    fn test_listener() {
+        let calls = Rc::new(Cell::new(0));
+        let listener = {
+            let calls = calls.clone();
+            Listener::new("click", move |event| {
+                calls.set(calls.get() + event.downcast_ref::<i32>().unwrap());
+            })
+        };
+
+        listener.call(&2);
+        listener.clone().call(&3);
+
+        assert_that!(calls.get()).is_equal_to(5);
+        assert_that!(listener.event()).is_equal_to("click");
+        assert_that!(listener.clone()).is_equal_to(listener.clone());
+        assert_that!(listener.clone()).is_not_equal_to(Listener::new("click", |_| {}));
+    }
+}
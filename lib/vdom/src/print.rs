@@ -0,0 +1,242 @@
+use std::fmt::Write;
+
+use crate::{VItem, VNode, VRef, VTree};
+
+impl VTree<VItem> {
+
+    pub fn dump(&self) -> String {
+        self.dump_tree(false)
+    }
+
+    pub fn dump_with_refs(&self) -> String {
+        self.dump_tree(true)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut output = String::from("digraph vtree {\n    node [shape=box];\n");
+        self.walk(|node, _| {
+            let id = String::from(&node.id);
+            writeln!(output, "    \"{}\" [label=\"{}\"];", id, escape_dot(&label(node, |value| format!("{:?}", value)))).unwrap();
+            if let Some(parent) = self.parent(&node.id) {
+                writeln!(output, "    \"{}\" -> \"{}\";", String::from(parent), id).unwrap();
+            }
+        });
+        output.push_str("}\n");
+        output
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let mut output = String::from("graph TD\n");
+        self.walk(|node, _| {
+            let id = mermaid_id(&node.id);
+            writeln!(output, "    {}[\"{}\"]", id, escape_mermaid(&label(node, |value| format!("\"{}\"", value)))).unwrap();
+            if let Some(parent) = self.parent(&node.id) {
+                writeln!(output, "    {} --> {}", mermaid_id(parent), id).unwrap();
+            }
+        });
+        output
+    }
+
+    fn dump_tree(&self, show_refs: bool) -> String {
+        let mut output = String::new();
+        self.walk(|node, depth| {
+            let line = match &node.item {
+                None => String::from("<?>"),
+                Some(VItem::Element { name, attributes, .. }) => {
+                    let mut line = format!("<{}", name);
+                    attributes.iter().for_each(|(name, value)| {
+                        write!(line, " {}={:?}", name, value).unwrap();
+                    });
+                    line.push('>');
+                    line
+                }
+                Some(VItem::Text { value }) => format!("{:?}", value),
+            };
+            output.push_str("  ".repeat(depth).as_str());
+            output.push_str(line.as_str());
+            if show_refs {
+                write!(output, " [{}]", String::from(&node.id)).unwrap();
+            }
+            output.push('\n');
+        });
+        output
+    }
+
+    fn walk<F>(&self, mut visit_fn: F) where F: FnMut(&VNode<VItem>, usize) {
+        let mut stack: Vec<(VRef, usize)> = Vec::new();
+        if let Some(root) = self.root {
+            stack.push((root, 0));
+        }
+        while let Some((node_ref, depth)) = stack.pop() {
+            if let Some(node) = self.nodes.get(&node_ref) {
+                visit_fn(node, depth);
+                self.children(&node_ref).iter().rev().for_each(|child| {
+                    stack.push((child.id, depth + 1))
+                });
+            }
+        }
+    }
+}
+
+fn label(node: &VNode, quote: fn(&str) -> String) -> String {
+    match &node.item {
+        None => String::from("?"),
+        Some(VItem::Element { name, attributes, .. }) => {
+            let mut label = name.clone();
+            attributes.iter().for_each(|(name, value)| {
+                write!(label, "\n{}={}", name, value).unwrap();
+            });
+            label
+        }
+        Some(VItem::Text { value }) => quote(value),
+    }
+}
+
+fn mermaid_id(vref: &VRef) -> String {
+    format!("n{}", vref.id.to_simple())
+}
+
+fn escape_dot(label: &str) -> String {
+    label.chars().fold(String::new(), |mut result, c| {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            _ => result.push(c),
+        }
+        result
+    })
+}
+
+fn escape_mermaid(label: &str) -> String {
+    label.chars().fold(String::new(), |mut result, c| {
+        match c {
+            // Mermaid takes entity codes within labels, backslashes are not escapes there.
+            '"' => result.push_str("#quot;"),
+            '#' => result.push_str("#35;"),
+            '&' => result.push_str("#amp;"),
+            '<' => result.push_str("#lt;"),
+            '>' => result.push_str("#gt;"),
+            '(' => result.push_str("#40;"),
+            ')' => result.push_str("#41;"),
+            '[' => result.push_str("#91;"),
+            ']' => result.push_str("#93;"),
+            '{' => result.push_str("#123;"),
+            '}' => result.push_str("#125;"),
+            '|' => result.push_str("#124;"),
+            '\n' => result.push_str("<br/>"),
+            _ => result.push(c),
+        }
+        result
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use crate::{VItem, VRef, VTree};
+
+    fn create_tree() -> (VTree, VRef, VRef, VRef) {
+        let mut tree = VTree::new();
+        let div = tree.create_random_node();
+        let p = tree.create_random_node();
+        let text = tree.create_random_node();
+
+        tree.set_root(&div);
+        tree.append_child(&div, &p);
+        tree.append_child(&p, &text);
+
+        tree.update_node(&div, Box::new(|node| {
+            node.item = Some(VItem::Element {
+                name: String::from("div"),
+                attributes: vec![(String::from("class"), String::from("container"))],
+                listeners: Vec::new(),
+            })
+        }));
+        tree.update_node(&p, Box::new(|node| {
+            node.item = Some(VItem::Element {
+                name: String::from("p"),
+                attributes: Vec::new(),
+                listeners: Vec::new(),
+            })
+        }));
+        tree.update_node(&text, Box::new(|node| {
+            node.item = Some(VItem::Text { value: String::from("Hello \"World\"") })
+        }));
+
+        (tree, div, p, text)
+    }
+
+    #[test]
+    fn test_dump() {
+        let (tree, _, _, _) = create_tree();
+
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div class=\"container\">\n",
+                "  <p>\n",
+                "    \"Hello \\\"World\\\"\"\n",
+            )));
+    }
+
+    #[test]
+    fn test_dump_with_refs() {
+        let (tree, div, p, text) = create_tree();
+
+        assert_that!(tree.dump_with_refs())
+            .is_equal_to(format!(
+                "<div class=\"container\"> [{}]\n  <p> [{}]\n    \"Hello \\\"World\\\"\" [{}]\n",
+                String::from(div), String::from(p), String::from(text)
+            ));
+    }
+
+    #[test]
+    fn test_dump_empty_tree() {
+        assert_that!(VTree::new().dump()).is_empty();
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let (tree, div, p, _) = create_tree();
+        let dot = tree.to_dot();
+
+        assert_that!(dot).starts_with("digraph vtree {\n");
+        assert_that!(dot).ends_with("}\n");
+        assert_that!(dot).contains(format!("\"{}\" [label=\"div\\nclass=container\"];", String::from(div)).as_str());
+        assert_that!(dot).contains(format!("\"{}\" -> \"{}\";", String::from(div), String::from(p)).as_str());
+        assert_that!(dot).contains("[label=\"\\\"Hello \\\\\\\"World\\\\\\\"\\\"\"];");
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let (tree, div, p, _) = create_tree();
+        let mermaid = tree.to_mermaid();
+        let div_id = format!("n{}", String::from(div).replace('-', ""));
+        let p_id = format!("n{}", String::from(p).replace('-', ""));
+
+        assert_that!(mermaid).starts_with("graph TD\n");
+        assert_that!(mermaid).contains(format!("    {}[\"div<br/>class=container\"]\n", div_id).as_str());
+        assert_that!(mermaid).contains(format!("    {} --> {}\n", div_id, p_id).as_str());
+        assert_that!(mermaid).contains("[\"#quot;Hello #quot;World#quot;#quot;\"]");
+    }
+
+    #[test]
+    fn test_to_mermaid_escapes_labels() {
+        let mut tree = VTree::new();
+        let a = tree.create_random_node();
+        let item = VItem::Element {
+            name: String::from("a"),
+            attributes: vec![(String::from("title"), String::from("say \"hi\" [0] (x) | <b> #1 {}"))],
+            listeners: Vec::new(),
+        };
+        tree.update_node(&a, Box::new(|node| node.item = Some(item)));
+        tree.set_root(&a);
+
+        assert_that!(tree.to_mermaid())
+            .is_equal_to(format!(
+                "graph TD\n    n{}[\"a<br/>title=say #quot;hi#quot; #91;0#93; #40;x#41; #124; #lt;b#gt; #35;1 #123;#125;\"]\n",
+                String::from(a).replace('-', "")
+            ));
+    }
+}
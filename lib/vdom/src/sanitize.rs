@@ -0,0 +1,410 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{Visit, VisitorMut, VItem, VRef, VTree};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Removal {
+    DroppedElement { node: VRef, name: String },
+    UnwrappedElement { node: VRef, name: String },
+    RemovedAttribute { node: VRef, element: String, name: String, value: String },
+    RemovedUrl { node: VRef, element: String, name: String, value: String },
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct Policy {
+    allowed_tags: HashSet<String>,
+    dropped_tags: HashSet<String>,
+    allowed_attributes: HashSet<String>,
+    allowed_tag_attributes: HashMap<String, HashSet<String>>,
+    url_attributes: HashSet<String>,
+    allowed_url_schemes: HashSet<String>,
+    container: String,
+}
+
+impl Policy {
+
+    pub fn new() -> Policy {
+        let mut policy = Policy::empty();
+        [
+            "a", "abbr", "b", "blockquote", "br", "button", "caption", "code", "dd", "del", "div", "dl", "dt",
+            "em", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "img", "input", "ins", "kbd", "label", "li",
+            "ol", "p", "pre", "q", "s", "small", "span", "strong", "sub", "sup", "table", "tbody", "td",
+            "tfoot", "th", "thead", "tr", "u", "ul",
+        ].iter().for_each(|tag| policy.allow_tag(tag));
+        [
+            "script", "style", "iframe", "frame", "frameset", "object", "embed", "applet", "noscript",
+            "template", "link", "meta", "base",
+        ].iter().for_each(|tag| policy.drop_tag(tag));
+        ["class", "id", "title", "lang", "dir"].iter().for_each(|attribute| policy.allow_attribute(attribute));
+        [
+            ("a", "href"), ("a", "rel"), ("a", "target"),
+            ("img", "src"), ("img", "alt"), ("img", "width"), ("img", "height"),
+            ("blockquote", "cite"), ("q", "cite"), ("ol", "start"),
+            ("td", "colspan"), ("td", "rowspan"), ("th", "colspan"), ("th", "rowspan"),
+            ("label", "for"),
+            ("input", "type"), ("input", "name"), ("input", "value"), ("input", "placeholder"),
+            ("input", "checked"), ("input", "disabled"),
+            ("button", "type"), ("button", "disabled"),
+        ].iter().for_each(|(tag, attribute)| policy.allow_tag_attribute(tag, attribute));
+        ["href", "src", "cite", "action", "formaction", "poster", "xlink:href"].iter()
+            .for_each(|attribute| policy.add_url_attribute(attribute));
+        ["http", "https", "mailto"].iter().for_each(|scheme| policy.allow_url_scheme(scheme));
+        policy
+    }
+
+    // Allows nothing, for policies built up from scratch.
+    pub fn empty() -> Policy {
+        Policy {
+            allowed_tags: HashSet::new(),
+            dropped_tags: HashSet::new(),
+            allowed_attributes: HashSet::new(),
+            allowed_tag_attributes: HashMap::new(),
+            url_attributes: HashSet::new(),
+            allowed_url_schemes: HashSet::new(),
+            container: String::from("div"),
+        }
+    }
+
+    pub fn allow_tag(&mut self, tag: &str) {
+        self.allowed_tags.insert(tag.to_ascii_lowercase());
+    }
+
+    pub fn drop_tag(&mut self, tag: &str) {
+        self.dropped_tags.insert(tag.to_ascii_lowercase());
+    }
+
+    pub fn allow_attribute(&mut self, attribute: &str) {
+        self.allowed_attributes.insert(attribute.to_ascii_lowercase());
+    }
+
+    pub fn allow_tag_attribute(&mut self, tag: &str, attribute: &str) {
+        self.allowed_tag_attributes.entry(tag.to_ascii_lowercase())
+            .or_default()
+            .insert(attribute.to_ascii_lowercase());
+    }
+
+    pub fn add_url_attribute(&mut self, attribute: &str) {
+        self.url_attributes.insert(attribute.to_ascii_lowercase());
+    }
+
+    pub fn allow_url_scheme(&mut self, scheme: &str) {
+        self.allowed_url_schemes.insert(scheme.to_ascii_lowercase());
+    }
+
+    // The element which takes the place of a disallowed root with several children.
+    pub fn set_container(&mut self, tag: &str) {
+        self.container = tag.to_ascii_lowercase();
+    }
+
+    pub fn is_tag_allowed(&self, tag: &str) -> bool {
+        self.allowed_tags.contains(&tag.to_ascii_lowercase())
+    }
+
+    pub fn is_tag_dropped(&self, tag: &str) -> bool {
+        self.dropped_tags.contains(&tag.to_ascii_lowercase())
+    }
+
+    pub fn is_attribute_allowed(&self, tag: &str, attribute: &str) -> bool {
+        let attribute = attribute.to_ascii_lowercase();
+        self.allowed_attributes.contains(&attribute)
+            || self.allowed_tag_attributes.get(&tag.to_ascii_lowercase())
+                .is_some_and(|attributes| attributes.contains(&attribute))
+    }
+
+    pub fn is_url_allowed(&self, attribute: &str, value: &str) -> bool {
+        if !self.url_attributes.contains(&attribute.to_ascii_lowercase()) {
+            return true
+        }
+        match url_scheme(value) {
+            None => true,
+            Some(scheme) => self.allowed_url_schemes.contains(&scheme),
+        }
+    }
+}
+
+impl Default for Policy {
+
+    fn default() -> Self {
+        Policy::new()
+    }
+}
+
+pub struct Sanitizer {
+    policy: Policy,
+    removals: Vec<Removal>,
+}
+
+impl Sanitizer {
+
+    pub fn new(policy: Policy) -> Sanitizer {
+        Sanitizer { policy, removals: Vec::new() }
+    }
+
+    pub fn removals(&self) -> &Vec<Removal> {
+        &self.removals
+    }
+
+    pub fn into_removals(self) -> Vec<Removal> {
+        self.removals
+    }
+
+    fn sanitize_attributes(&mut self, tree: &mut VTree, node: &VRef) {
+        if let Some(VItem::Element { name, attributes, .. }) = tree.item_mut(node) {
+            let policy = &self.policy;
+            let removals = &mut self.removals;
+            attributes.retain(|(attribute, value)| {
+                if !policy.is_attribute_allowed(name, attribute) {
+                    removals.push(Removal::RemovedAttribute {
+                        node: *node,
+                        element: name.clone(),
+                        name: attribute.clone(),
+                        value: value.clone(),
+                    });
+                    false
+                }
+                else if !policy.is_url_allowed(attribute, value) {
+                    removals.push(Removal::RemovedUrl {
+                        node: *node,
+                        element: name.clone(),
+                        name: attribute.clone(),
+                        value: value.clone(),
+                    });
+                    false
+                }
+                else {
+                    true
+                }
+            });
+        }
+    }
+}
+
+impl VisitorMut for Sanitizer {
+
+    fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+        let name = match tree.item(node) {
+            Some(VItem::Element { name, .. }) => name.clone(),
+            _ => return Visit::Continue,
+        };
+        if self.policy.is_tag_dropped(&name) {
+            tree.remove_node(node);
+            self.removals.push(Removal::DroppedElement { node: *node, name });
+            return Visit::SkipChildren
+        }
+        if self.policy.is_tag_allowed(&name) {
+            self.sanitize_attributes(tree, node);
+        }
+        Visit::Continue
+    }
+
+    fn leave_element(&mut self, tree: &mut VTree, node: &VRef) {
+        let name = match tree.item(node) {
+            Some(VItem::Element { name, .. }) => name.clone(),
+            _ => return,
+        };
+        if self.policy.is_tag_allowed(&name) {
+            return
+        }
+        // Disallowed elements are unwrapped after their children have been sanitized.
+        let children: Vec<VRef> = tree.children(node).iter().map(|child| child.id).collect();
+        match tree.parent(node).cloned() {
+            Some(parent) => {
+                children.iter().for_each(|child| tree.insert_before(&parent, child, node));
+                tree.remove_node(node);
+                self.removals.push(Removal::UnwrappedElement { node: *node, name });
+            }
+            None if children.len() == 1 => {
+                // The root can only be unwrapped when it has a single child to take its place.
+                tree.remove_child(node, &children[0]);
+                tree.set_root(&children[0]);
+                tree.remove_node(node);
+                self.removals.push(Removal::UnwrappedElement { node: *node, name });
+            }
+            None if children.is_empty() => {
+                tree.remove_node(node);
+                self.removals.push(Removal::DroppedElement { node: *node, name });
+            }
+            None => {
+                // Otherwise the children are wrapped in the policy's container, which becomes the root.
+                let container = tree.create_random_node();
+                let item = VItem::Element { name: self.policy.container.clone(), attributes: Vec::new(), listeners: Vec::new() };
+                tree.update_node(&container, Box::new(|container| container.item = Some(item)));
+                children.iter().for_each(|child| tree.append_child(&container, child));
+                tree.set_root(&container);
+                tree.remove_node(node);
+                self.removals.push(Removal::UnwrappedElement { node: *node, name });
+            }
+        }
+    }
+}
+
+impl VTree<VItem> {
+
+    pub fn sanitize(&mut self, policy: &Policy) -> Vec<Removal> {
+        let mut sanitizer = Sanitizer::new(policy.clone());
+        self.visit_mut(&mut sanitizer);
+        sanitizer.into_removals()
+    }
+}
+
+fn url_scheme(value: &str) -> Option<String> {
+    // Browsers ignore whitespace and control characters within the scheme, e.g. "java\tscript:".
+    let normalized: String = value.chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    let end = normalized.find([':', '/', '?', '#'])?;
+    if normalized[end..].starts_with(':') {
+        Some(normalized[..end].to_ascii_lowercase())
+    }
+    else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use speculoos::prelude::*;
+
+    use crate::{Pipeline, Policy, Removal, Sanitizer, VTree};
+    use crate::testing::{element, text};
+
+    #[test]
+    fn test_sanitize_default_policy() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "content"), ("onclick", "steal()")]);
+        let script = element(&mut tree, Some(&div), "script", &[]);
+        text(&mut tree, &script, "alert(1)");
+        let link = element(&mut tree, Some(&div), "a", &[("href", " JaVa\tScript:alert(1)"), ("title", "x")]);
+        text(&mut tree, &link, "click");
+        let font = element(&mut tree, Some(&div), "font", &[("color", "red")]);
+        text(&mut tree, &font, "Hello ");
+        let b = element(&mut tree, Some(&font), "b", &[("onmouseover", "x()")]);
+        text(&mut tree, &b, "World");
+        let img = element(&mut tree, Some(&div), "img", &[("src", "https://example.com/a.png"), ("alt", "a")]);
+
+        let removals = tree.sanitize(&Policy::default());
+
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div class=\"content\">\n",
+                "  <a title=\"x\">\n",
+                "    \"click\"\n",
+                "  \"Hello \"\n",
+                "  <b>\n",
+                "    \"World\"\n",
+                "  <img src=\"https://example.com/a.png\" alt=\"a\">\n",
+            )));
+        assert_that!(tree.contains_node(&img)).is_true();
+        assert_that!(removals)
+            .is_equal_to(vec![
+                Removal::RemovedAttribute {
+                    node: div,
+                    element: String::from("div"),
+                    name: String::from("onclick"),
+                    value: String::from("steal()"),
+                },
+                Removal::DroppedElement { node: script, name: String::from("script") },
+                Removal::RemovedUrl {
+                    node: link,
+                    element: String::from("a"),
+                    name: String::from("href"),
+                    value: String::from(" JaVa\tScript:alert(1)"),
+                },
+                Removal::RemovedAttribute {
+                    node: b,
+                    element: String::from("b"),
+                    name: String::from("onmouseover"),
+                    value: String::from("x()"),
+                },
+                Removal::UnwrappedElement { node: font, name: String::from("font") },
+            ]);
+    }
+
+    #[test]
+    fn test_sanitize_custom_policy() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("data-id", "1"), ("style", "color: red")]);
+        element(&mut tree, Some(&div), "a", &[("href", "ftp://example.com"), ("download", "")]);
+        element(&mut tree, Some(&div), "a", &[("href", "/relative/path:with-colon")]);
+
+        let mut policy = Policy::empty();
+        policy.allow_tag("div");
+        policy.allow_tag("a");
+        policy.allow_attribute("data-id");
+        policy.allow_tag_attribute("a", "href");
+        policy.add_url_attribute("href");
+        policy.allow_url_scheme("ftp");
+
+        let removals = tree.sanitize(&policy);
+
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div data-id=\"1\">\n",
+                "  <a href=\"ftp://example.com\">\n",
+                "  <a href=\"/relative/path:with-colon\">\n",
+            )));
+        assert_that!(removals).has_length(2);
+    }
+
+    #[test]
+    fn test_sanitize_root() {
+        let mut tree = VTree::new();
+        let root = element(&mut tree, None, "section", &[]);
+        let p = element(&mut tree, Some(&root), "p", &[]);
+        text(&mut tree, &p, "Hello");
+
+        let removals = tree.sanitize(&Policy::default());
+
+        assert_that!(tree.get_root()).is_equal_to(Some(p));
+        assert_that!(removals)
+            .is_equal_to(vec![Removal::UnwrappedElement { node: root, name: String::from("section") }]);
+
+        let mut tree = VTree::new();
+        let root = element(&mut tree, None, "script", &[]);
+
+        tree.sanitize(&Policy::default());
+
+        assert_that!(tree.contains_node(&root)).is_false();
+        assert_that!(tree.get_root()).is_none();
+
+        let mut tree = VTree::new();
+        let root = element(&mut tree, None, "article", &[("onload", "x()")]);
+        let p = element(&mut tree, Some(&root), "p", &[]);
+        text(&mut tree, &p, "Hello");
+        element(&mut tree, Some(&root), "script", &[]);
+        text(&mut tree, &root, "World");
+
+        let removals = tree.sanitize(&Policy::new());
+
+        assert_that!(tree.contains_node(&root)).is_false();
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div>\n",
+                "  <p>\n",
+                "    \"Hello\"\n",
+                "  \"World\"\n",
+            )));
+        assert_that!(removals.last())
+            .is_equal_to(Some(&Removal::UnwrappedElement { node: root, name: String::from("article") }));
+    }
+
+    #[test]
+    fn test_default_policy() {
+        assert_that!(Policy::new()).is_equal_to(Policy::default());
+        assert_that!(Policy::new().is_tag_allowed("p")).is_true();
+        assert_that!(Policy::empty().is_tag_allowed("p")).is_false();
+    }
+
+    #[test]
+    fn test_sanitizer_in_pipeline() {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[]);
+        element(&mut tree, Some(&div), "iframe", &[("src", "https://example.com")]);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.add_pass(Sanitizer::new(Policy::default()));
+        pipeline.run(&mut tree);
+
+        assert_that!(tree.dump()).is_equal_to(String::from("<div>\n"));
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{VItem, VRef, VTree};
+
+pub fn element(tree: &mut VTree, parent: Option<&VRef>, name: &str, attributes: &[(&str, &str)]) -> VRef {
+    let node = tree.create_random_node();
+    let item = VItem::Element {
+        name: String::from(name),
+        attributes: attributes.iter()
+            .map(|(name, value)| (String::from(*name), String::from(*value)))
+            .collect(),
+        listeners: Vec::new(),
+    };
+    tree.update_node(&node, Box::new(|node| node.item = Some(item)));
+    match parent {
+        None => tree.set_root(&node),
+        Some(parent) => tree.append_child(parent, &node),
+    }
+    node
+}
+
+pub fn text(tree: &mut VTree, parent: &VRef, value: &str) -> VRef {
+    let node = tree.create_random_node();
+    let item = VItem::Text { value: String::from(value) };
+    tree.update_node(&node, Box::new(|node| node.item = Some(item)));
+    tree.append_child(parent, &node);
+    node
+}
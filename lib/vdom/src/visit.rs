@@ -0,0 +1,412 @@
+use crate::{VItem, VNode, VRef, VTree};
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Visit {
+    Continue,
+    SkipChildren,
+}
+
+pub trait Visitor {
+
+    fn enter_element(&mut self, _node: &VNode, _name: &str, _attributes: &[(String, String)]) -> Visit {
+        Visit::Continue
+    }
+
+    fn leave_element(&mut self, _node: &VNode, _name: &str, _attributes: &[(String, String)]) {}
+
+    fn enter_text(&mut self, _node: &VNode, _value: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn leave_text(&mut self, _node: &VNode, _value: &str) {}
+}
+
+pub trait VisitorMut {
+
+    fn enter_element(&mut self, _tree: &mut VTree, _node: &VRef) -> Visit {
+        Visit::Continue
+    }
+
+    fn leave_element(&mut self, _tree: &mut VTree, _node: &VRef) {}
+
+    fn enter_text(&mut self, _tree: &mut VTree, _node: &VRef) -> Visit {
+        Visit::Continue
+    }
+
+    fn leave_text(&mut self, _tree: &mut VTree, _node: &VRef) {}
+}
+
+// Each pass skips only the subtrees it asked to skip, the other passes still visit them.
+#[derive(Default)]
+pub struct Pipeline {
+    passes: Vec<Box<dyn VisitorMut>>,
+    // The node whose children each pass is skipping.
+    skipping: Vec<Option<VRef>>,
+}
+
+impl Pipeline {
+
+    pub fn new() -> Pipeline {
+        Pipeline { passes: Vec::new(), skipping: Vec::new() }
+    }
+
+    pub fn add_pass<P>(&mut self, pass: P) where P: VisitorMut + 'static {
+        self.passes.push(Box::new(pass));
+        self.skipping.push(None);
+    }
+
+    pub fn run(&mut self, tree: &mut VTree) {
+        tree.visit_mut(self);
+        self.skipping.iter_mut().for_each(|skipping| *skipping = None);
+    }
+
+    fn enter_all(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+        for (pass, skipping) in self.passes.iter_mut().zip(self.skipping.iter_mut()) {
+            if skipping.is_some() {
+                continue
+            }
+            let visit = match tree.item(node) {
+                Some(VItem::Element { .. }) => pass.enter_element(tree, node),
+                Some(VItem::Text { .. }) => pass.enter_text(tree, node),
+                None => Visit::Continue,
+            };
+            if !tree.contains_node(node) {
+                // A removed node is not left, so the passes skipping it would never resume.
+                self.resume(node);
+                return Visit::SkipChildren
+            }
+            if visit == Visit::SkipChildren {
+                *skipping = Some(*node);
+            }
+        }
+        match self.skipping.iter().all(|skipping| skipping.is_some()) {
+            true => Visit::SkipChildren,
+            false => Visit::Continue,
+        }
+    }
+
+    fn leave_all(&mut self, tree: &mut VTree, node: &VRef) {
+        for (pass, skipping) in self.passes.iter_mut().zip(self.skipping.iter()).rev() {
+            if skipping.is_some_and(|skipping| skipping != *node) {
+                continue
+            }
+            match tree.item(node) {
+                Some(VItem::Element { .. }) => pass.leave_element(tree, node),
+                Some(VItem::Text { .. }) => pass.leave_text(tree, node),
+                None => {}
+            }
+            if !tree.contains_node(node) {
+                break
+            }
+        }
+        self.resume(node);
+    }
+
+    fn resume(&mut self, node: &VRef) {
+        self.skipping.iter_mut()
+            .filter(|skipping| **skipping == Some(*node))
+            .for_each(|skipping| *skipping = None);
+    }
+}
+
+impl VisitorMut for Pipeline {
+
+    fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+        self.enter_all(tree, node)
+    }
+
+    fn leave_element(&mut self, tree: &mut VTree, node: &VRef) {
+        self.leave_all(tree, node)
+    }
+
+    fn enter_text(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+        self.enter_all(tree, node)
+    }
+
+    fn leave_text(&mut self, tree: &mut VTree, node: &VRef) {
+        self.leave_all(tree, node)
+    }
+}
+
+impl VTree<VItem> {
+
+    pub fn visit<V>(&self, visitor: &mut V) where V: Visitor {
+        if let Some(root) = self.root {
+            walk(self, &root, visitor)
+        }
+    }
+
+    pub fn visit_mut<V>(&mut self, visitor: &mut V) where V: VisitorMut {
+        if let Some(root) = self.root {
+            walk_mut(self, &root, visitor)
+        }
+    }
+}
+
+fn walk<V>(tree: &VTree, node_ref: &VRef, visitor: &mut V) where V: Visitor {
+    if let Some(node) = tree.nodes.get(node_ref) {
+        match &node.item {
+            Some(VItem::Element { name, attributes, .. }) => {
+                if visitor.enter_element(node, name, attributes) == Visit::Continue {
+                    walk_children(tree, node_ref, visitor);
+                }
+                visitor.leave_element(node, name, attributes);
+            }
+            Some(VItem::Text { value }) => {
+                if visitor.enter_text(node, value) == Visit::Continue {
+                    walk_children(tree, node_ref, visitor);
+                }
+                visitor.leave_text(node, value);
+            }
+            None => walk_children(tree, node_ref, visitor),
+        }
+    }
+}
+
+fn walk_children<V>(tree: &VTree, node_ref: &VRef, visitor: &mut V) where V: Visitor {
+    if let Some(children) = tree.children.get(node_ref) {
+        children.iter().for_each(|child| walk(tree, child, visitor));
+    }
+}
+
+fn walk_mut<V>(tree: &mut VTree, node_ref: &VRef, visitor: &mut V) where V: VisitorMut {
+    let visit = match tree.item(node_ref) {
+        Some(VItem::Element { .. }) => visitor.enter_element(tree, node_ref),
+        Some(VItem::Text { .. }) => visitor.enter_text(tree, node_ref),
+        None => Visit::Continue,
+    };
+
+    // The visitor may have removed the node.
+    if !tree.contains_node(node_ref) {
+        return
+    }
+
+    if visit == Visit::Continue {
+        // Children are snapshotted so nodes inserted by the visitor are not visited,
+        // and nodes it moved away from this parent are skipped.
+        let children = tree.children.get(node_ref).cloned().unwrap_or_default();
+        children.iter().for_each(|child| {
+            if tree.parent(child) == Some(node_ref) {
+                walk_mut(tree, child, visitor)
+            }
+        });
+    }
+
+    match tree.item(node_ref) {
+        Some(VItem::Element { .. }) => visitor.leave_element(tree, node_ref),
+        Some(VItem::Text { .. }) => visitor.leave_text(tree, node_ref),
+        None => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use speculoos::prelude::*;
+
+    use crate::{Pipeline, Visit, Visitor, VisitorMut, VItem, VNode, VRef, VTree};
+    use crate::testing::{element, text};
+
+    fn create_tree() -> VTree {
+        let mut tree = VTree::new();
+        let div = element(&mut tree, None, "div", &[("class", "container"), ("data-debug", "1")]);
+        let table = element(&mut tree, Some(&div), "table", &[]);
+        let tr = element(&mut tree, Some(&table), "tr", &[("data-debug", "2")]);
+        text(&mut tree, &tr, "cell");
+        let p = element(&mut tree, Some(&div), "p", &[]);
+        text(&mut tree, &p, "Hello");
+        tree
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+    }
+
+    impl Visitor for Recorder {
+
+        fn enter_element(&mut self, _node: &VNode, name: &str, _attributes: &[(String, String)]) -> Visit {
+            self.events.push(format!("<{}>", name));
+            if name == "table" { Visit::SkipChildren } else { Visit::Continue }
+        }
+
+        fn leave_element(&mut self, _node: &VNode, name: &str, _attributes: &[(String, String)]) {
+            self.events.push(format!("</{}>", name));
+        }
+
+        fn enter_text(&mut self, _node: &VNode, value: &str) -> Visit {
+            self.events.push(format!("{:?}", value));
+            Visit::Continue
+        }
+    }
+
+    struct AddTestId {
+        visited: Rc<RefCell<usize>>,
+    }
+
+    impl VisitorMut for AddTestId {
+
+        fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+            *self.visited.borrow_mut() += 1;
+            if let Some(item) = tree.item_mut(node) {
+                if let VItem::Element { name, .. } = item {
+                    let name = name.clone();
+                    item.set_attribute("data-testid", name.as_str());
+                }
+            }
+            Visit::Continue
+        }
+    }
+
+    struct StripDebug;
+
+    impl VisitorMut for StripDebug {
+
+        fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+            if let Some(item) = tree.item_mut(node) {
+                item.remove_attribute("data-debug");
+            }
+            Visit::Continue
+        }
+    }
+
+    struct WrapTables;
+
+    impl VisitorMut for WrapTables {
+
+        fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+            let is_table = matches!(tree.item(node), Some(VItem::Element { name, .. }) if name == "table");
+            if let (true, Some(parent)) = (is_table, tree.parent(node).cloned()) {
+                let wrapper = tree.create_random_node();
+                tree.update_node(&wrapper, Box::new(|wrapper| {
+                    wrapper.item = Some(VItem::Element {
+                        name: String::from("div"),
+                        attributes: vec![(String::from("class"), String::from("table-container"))],
+                        listeners: Vec::new(),
+                    })
+                }));
+                tree.insert_before(&parent, &wrapper, node);
+                tree.append_child(&wrapper, node);
+            }
+            Visit::Continue
+        }
+    }
+
+    #[test]
+    fn test_visit_order() {
+        let tree = create_tree();
+        let mut recorder = Recorder::default();
+
+        tree.visit(&mut recorder);
+
+        assert_that!(recorder.events)
+            .is_equal_to(vec![
+                String::from("<div>"),
+                String::from("<table>"),
+                String::from("</table>"),
+                String::from("<p>"),
+                String::from("\"Hello\""),
+                String::from("</p>"),
+                String::from("</div>"),
+            ]);
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut tree = create_tree();
+        let visited = Rc::new(RefCell::new(0));
+        let mut pipeline = Pipeline::new();
+
+        pipeline.add_pass(AddTestId { visited: visited.clone() });
+        pipeline.add_pass(StripDebug);
+        pipeline.add_pass(WrapTables);
+        pipeline.run(&mut tree);
+
+        assert_that!(*visited.borrow()).is_equal_to(4);
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div class=\"container\" data-testid=\"div\">\n",
+                "  <div class=\"table-container\">\n",
+                "    <table data-testid=\"table\">\n",
+                "      <tr data-testid=\"tr\">\n",
+                "        \"cell\"\n",
+                "  <p data-testid=\"p\">\n",
+                "    \"Hello\"\n",
+            )));
+    }
+
+    #[test]
+    fn test_pipeline_skips_per_pass() {
+
+        struct SkipTables {
+            visited: Rc<RefCell<Vec<String>>>,
+        }
+
+        impl VisitorMut for SkipTables {
+
+            fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+                match tree.item(node) {
+                    Some(VItem::Element { name, .. }) => {
+                        self.visited.borrow_mut().push(name.clone());
+                        if name == "table" { Visit::SkipChildren } else { Visit::Continue }
+                    }
+                    _ => Visit::Continue,
+                }
+            }
+        }
+
+        let mut tree = create_tree();
+        let skipped = Rc::new(RefCell::new(Vec::new()));
+        let mut pipeline = Pipeline::new();
+
+        pipeline.add_pass(SkipTables { visited: skipped.clone() });
+        pipeline.add_pass(StripDebug);
+        pipeline.run(&mut tree);
+
+        assert_that!(*skipped.borrow())
+            .is_equal_to(vec![String::from("div"), String::from("table"), String::from("p")]);
+        assert_that!(tree.dump())
+            .is_equal_to(String::from(concat!(
+                "<div class=\"container\">\n",
+                "  <table>\n",
+                "    <tr>\n",
+                "      \"cell\"\n",
+                "  <p>\n",
+                "    \"Hello\"\n",
+            )));
+    }
+
+    #[test]
+    fn test_visit_mut_removed_node() {
+
+        struct RemoveTables;
+
+        impl VisitorMut for RemoveTables {
+
+            fn enter_element(&mut self, tree: &mut VTree, node: &VRef) -> Visit {
+                if matches!(tree.item(node), Some(VItem::Element { name, .. }) if name == "table") {
+                    tree.remove_node(node);
+                }
+                Visit::Continue
+            }
+        }
+
+        let mut tree = create_tree();
+        let mut recorder = Recorder::default();
+
+        tree.visit_mut(&mut RemoveTables);
+        tree.visit(&mut recorder);
+
+        assert_that!(tree.nodes()).has_length(3);
+        assert_that!(recorder.events)
+            .is_equal_to(vec![
+                String::from("<div>"),
+                String::from("<p>"),
+                String::from("\"Hello\""),
+                String::from("</p>"),
+                String::from("</div>"),
+            ]);
+    }
+}
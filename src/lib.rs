@@ -1,14 +1,15 @@
 extern crate wasm_bindgen;
 
-use std::collections::{HashMap, VecDeque};
-use log::info;
-use wasm_bindgen::JsCast;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use log::{error, info};
 use wasm_bindgen::prelude::*;
-use web_sys::{Element, Event, HtmlButtonElement};
+use web_sys::Element;
 
 use html_macro::html;
-use vdom::{VNode, VRef, VTree};
-use vdom_link::VNodeLink;
+use vdom::{VItem, VRef, VTree};
+use vdom_link::{AnimationFrames, Error, EventMode, Invalidator, Mount, MountOptions, Scheduler};
 
 #[wasm_bindgen]
 extern {
@@ -16,40 +17,16 @@ extern {
 }
 
 #[wasm_bindgen]
-pub fn greet() {
+pub fn greet() -> Result<(), JsValue> {
 
     let window = web_sys::window().expect("no global `window` exists");
     let document = window.document().expect("should have a document on window");
     let body = document.body().expect("document should have a body");
     let app = document.get_elements_by_tag_name("app").item(0).expect("docuemnt should have app");
 
-    let mut clicks = 0;
-
-    let div = document.create_element("div")
-        .expect("div created");
-
-    div.set_inner_html(&String::from(format!("Click count: {:?}", clicks)));
-    div.set_class_name("title has-text-grey-lighter");
-    app.append_child(&div);
-
-    let button: Element = {
-        let element = document.create_element("button").expect("button created");
-        let button_element: &HtmlButtonElement = element.dyn_ref::<HtmlButtonElement>().expect("has to be a button");
-        element.set_inner_html("Click Me!");
-        element.set_class_name("button");
-
-        let callback = Closure::wrap(Box::new(
-            move |event: Event| {
-                clicks += 1;
-                div.set_inner_html(&String::from(format!("Click count: {:?}", clicks)));
-                info!("Clicked!: {:?}", clicks);
-            }
-        ) as Box<dyn FnMut(Event)>);
-
-        button_element.set_onclick(Some(callback.as_ref().unchecked_ref()));
-        callback.forget(); // TODO: Remove call to forget (memory leak).
-        element
-    };
+    let scheduler = Scheduler::new(AnimationFrames::new(&window));
+    let counter = Rc::new(Counter::new(&scheduler));
+    counter.mount(&app)?;
 
     let tree: VTree = html! {
         <div class="container">
@@ -79,31 +56,139 @@ pub fn greet() {
         </div>
     };
 
-    let mut elements: HashMap<VRef, Element> = HashMap::new();
-    let mut render_queue: VecDeque<VRef> = VecDeque::new();
-    let root_node = tree.get_root().expect("Root node.");
+    let login = vdom_link::mount(&tree, &app)?;
+
+    info!("Tree:\n{}", tree.dump());
+    info!("Mounted {} nodes with {:?}.", login.len(), login.operations());
+
+    info!("app: {:?}", app);
+
+    APP.with(|state| state.replace(Some(App { counter, login })));
+    Ok(())
+}
+
+thread_local! {
+    static APP: RefCell<Option<App>> = const { RefCell::new(None) };
+}
+
+#[wasm_bindgen]
+pub fn teardown() -> Result<(), JsValue> {
+    if let Some(mut app) = APP.with(|state| state.take()) {
+        app.counter.unmount()?;
+        app.login.unmount()?;
+        info!("Unmounted app.");
+    }
+    Ok(())
+}
+
+// Owns the mounts, and thereby their event listeners, for as long as the app is running.
+struct App {
+    counter: Rc<Counter>,
+    login: Mount,
+}
 
-    render_queue.push_back(root_node);
+struct Counter {
+    refs: [VRef; 5],
+    clicks: Cell<u32>,
+    mount: RefCell<Option<Mount>>,
+    // Invalidators only hold on to the scheduler weakly.
+    scheduler: Scheduler,
+    invalidator: RefCell<Option<Invalidator>>,
+}
 
-    while !render_queue.is_empty() {
-        let node_ref = render_queue.pop_front().expect("Ref");
-        let node: VNode = tree.get_node(&node_ref).expect("Node");
-        let element = node.upsert(&document);
+impl Counter {
 
-        if let Some(parent_ref) = tree.parent(&node_ref) {
-            let parent_element = elements.get(parent_ref).expect("Parent element");
-            parent_element.append_child(&element);
+    fn new(scheduler: &Scheduler) -> Counter {
+        Counter {
+            refs: [VRef::random(), VRef::random(), VRef::random(), VRef::random(), VRef::random()],
+            clicks: Cell::new(0),
+            mount: RefCell::new(None),
+            scheduler: scheduler.clone(),
+            invalidator: RefCell::new(None),
         }
+    }
 
-        info!("Node: {:?}", node);
-        tree.children(&node_ref).iter().for_each(|child_ref| {
-            render_queue.push_back(child_ref.id)
-        });
-        elements.insert(node_ref, element);
+    fn mount(self: &Rc<Self>, container: &Element) -> Result<(), Error> {
+        let options = MountOptions { events: EventMode::Delegated, ..MountOptions::default() };
+        let mount = vdom_link::mount_with_options(&self.tree(), container, options)?;
+        self.mount.replace(Some(mount));
+        let counter = Rc::downgrade(self);
+        self.invalidator.replace(Some(self.scheduler.render(move || {
+            if let Some(counter) = counter.upgrade() {
+                counter.render();
+            }
+        })));
+        Ok(())
     }
 
-    let root_element = elements.get(&root_node).expect("Element");
-    app.append_child(&root_element);
+    fn invalidate(&self) {
+        if let Some(invalidator) = self.invalidator.borrow().as_ref() {
+            invalidator.invalidate();
+        }
+    }
 
-    info!("app: {:?}", app);
+    fn unmount(&self) -> Result<(), Error> {
+        self.invalidator.take();
+        match self.mount.take() {
+            Some(mut mount) => mount.unmount(),
+            None => Ok(()),
+        }
+    }
+
+    fn render(self: &Rc<Self>) {
+        let tree = self.tree();
+        if let Some(mount) = self.mount.borrow_mut().as_mut() {
+            if let Err(error) = mount.update(&tree) {
+                error!("Failed to render counter: {}", error);
+            }
+        }
+    }
+
+    fn tree(self: &Rc<Self>) -> VTree {
+        let [root, title, count, button, label] = self.refs;
+        let counter = Rc::downgrade(self);
+        let on_click = vdom_link::on("click", move |_| {
+            if let Some(counter) = counter.upgrade() {
+                counter.clicks.set(counter.clicks.get() + 1);
+                info!("Clicked!: {:?}", counter.clicks.get());
+                counter.invalidate();
+            }
+        });
+
+        let mut tree = VTree::new();
+        [root, title, count, button, label].iter().for_each(|node| {
+            tree.create_node(node);
+        });
+        tree.set_root(&root);
+        tree.append_child(&root, &title);
+        tree.append_child(&title, &count);
+        tree.append_child(&root, &button);
+        tree.append_child(&button, &label);
+
+        tree.update_node(&root, Box::new(|node| {
+            node.item = Some(VItem::Element { name: String::from("div"), attributes: Vec::new(), listeners: Vec::new() })
+        }));
+        tree.update_node(&title, Box::new(|node| {
+            node.item = Some(VItem::Element {
+                name: String::from("div"),
+                attributes: vec![(String::from("class"), String::from("title has-text-grey-lighter"))],
+                listeners: Vec::new(),
+            })
+        }));
+        let text = format!("Click count: {:?}", self.clicks.get());
+        tree.update_node(&count, Box::new(|node| {
+            node.item = Some(VItem::Text { value: text })
+        }));
+        tree.update_node(&button, Box::new(|node| {
+            node.item = Some(VItem::Element {
+                name: String::from("button"),
+                attributes: vec![(String::from("class"), String::from("button"))],
+                listeners: vec![on_click],
+            })
+        }));
+        tree.update_node(&label, Box::new(|node| {
+            node.item = Some(VItem::Text { value: String::from("Click Me!") })
+        }));
+        tree
+    }
 }
@@ -7,5 +7,5 @@ fn main() {
 
     info!("Initializing");
 
-    greet();
+    greet().expect("Successful start of the app.");
 }